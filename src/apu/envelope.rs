@@ -0,0 +1,43 @@
+// Volume envelope of the square and noise channels, stepped at 64 Hz
+#[derive(Debug, Default)]
+pub struct Envelope {
+    initial: u8,
+    increase: bool,
+    period: u8,
+    timer: u8,
+    pub volume: u8,
+}
+
+impl Envelope {
+    pub fn write(&mut self, value: u8) {
+        self.initial = value >> 4;
+        self.increase = value & 0b1000 != 0;
+        self.period = value & 0b111;
+    }
+
+    // The DAC is off when the upper five bits of NRx2 are all clear
+    pub fn dac_enabled(&self) -> bool {
+        self.initial != 0 || self.increase
+    }
+
+    pub fn trigger(&mut self) {
+        self.volume = self.initial;
+        self.timer = self.period;
+    }
+
+    pub fn step(&mut self) {
+        if self.period == 0 {
+            return
+        }
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer > 0 {
+            return
+        }
+        self.timer = self.period;
+        if self.increase && self.volume < 15 {
+            self.volume += 1;
+        } else if !self.increase && self.volume > 0 {
+            self.volume -= 1;
+        }
+    }
+}
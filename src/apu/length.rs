@@ -0,0 +1,36 @@
+// Silences a channel after a programmable number of 256 Hz steps
+#[derive(Debug)]
+pub struct LengthCounter {
+    max: u16,
+    counter: u16,
+    pub enabled: bool,
+}
+
+impl LengthCounter {
+    pub fn new(max: u16) -> LengthCounter {
+        LengthCounter {
+            max,
+            counter: 0,
+            enabled: false,
+        }
+    }
+
+    pub fn load(&mut self, value: u8) {
+        self.counter = self.max - (value as u16 & (self.max - 1));
+    }
+
+    pub fn trigger(&mut self) {
+        if self.counter == 0 {
+            self.counter = self.max;
+        }
+    }
+
+    // Returns true when the counter runs out and the channel should stop
+    pub fn step(&mut self) -> bool {
+        if !self.enabled || self.counter == 0 {
+            return false
+        }
+        self.counter -= 1;
+        self.counter == 0
+    }
+}
@@ -0,0 +1,301 @@
+mod envelope;
+mod length;
+mod noise;
+mod square;
+mod wave;
+
+use constants::*;
+use self::noise::Noise;
+use self::square::Square;
+use self::wave::Wave;
+use std::vec::Drain;
+
+pub const DEFAULT_SAMPLE_RATE: usize = 44_100;
+
+// Length, sweep and envelope are clocked by a 512 Hz frame sequencer
+const FRAME_SEQUENCER_CYCLES: usize = CLOCK_SPEED / 512;
+
+const NR52_POWER: u8 = 0b1000_0000;
+
+// Unused register slots between the channels
+const MREG_NR20: usize = 0xff15;
+const MREG_NR40: usize = 0xff1f;
+
+pub struct Apu {
+    square1: Square,
+    square2: Square,
+    wave: Wave,
+    noise: Noise,
+    // NR10 through NR51 as last written
+    regs: [u8; MREG_NR52 - MREG_NR10],
+    powered: bool,
+    sequencer_cycles: usize,
+    sequencer_step: u8,
+    sample_rate: usize,
+    sample_clock: usize,
+    samples: Vec<f32>,
+}
+
+impl Apu {
+    pub fn new(sample_rate: usize) -> Apu {
+        Apu {
+            square1: Square::new(true),
+            square2: Square::new(false),
+            wave: Wave::new(),
+            noise: Noise::new(),
+            regs: [0; MREG_NR52 - MREG_NR10],
+            powered: false,
+            sequencer_cycles: 0,
+            sequencer_step: 0,
+            sample_rate,
+            sample_clock: 0,
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: usize) {
+        self.sample_rate = sample_rate;
+        self.sample_clock = 0;
+        self.samples.clear();
+    }
+
+    // Interleaved left and right samples in the range -1.0 to 1.0
+    pub fn drain_samples(&mut self) -> Drain<'_, f32> {
+        self.samples.drain(..)
+    }
+
+    pub fn read(&self, addr: usize) -> u8 {
+        match addr {
+            MREG_NR52 => {
+                let mut value = if self.powered { NR52_POWER } else { 0 };
+                let channels = [self.square1.enabled, self.square2.enabled,
+                                self.wave.enabled, self.noise.enabled];
+                for (i, _) in channels.iter().enumerate().filter(|&(_, &on)| on) {
+                    value |= 1 << i;
+                }
+                value
+            },
+            MREG_NR10..=MREG_NR51 => self.regs[addr - MREG_NR10],
+            MREG_WAV00..=MREG_WAV15 => self.wave.ram[addr - MREG_WAV00],
+            _ => 0xff,
+        }
+    }
+
+    pub fn write(&mut self, addr: usize, value: u8) {
+        match addr {
+            MREG_NR52 => self.set_power(value & NR52_POWER != 0),
+            MREG_WAV00..=MREG_WAV15 => self.wave.ram[addr - MREG_WAV00] = value,
+            // The DMG keeps the length counters writable while powered off
+            MREG_NR11 if !self.powered => self.square1.write_length(value),
+            MREG_NR21 if !self.powered => self.square2.write_length(value),
+            MREG_NR31 if !self.powered => self.wave.write_length(value),
+            MREG_NR41 if !self.powered => self.noise.write_length(value),
+            _ if !self.powered => (),
+            MREG_NR10..=MREG_NR51 => {
+                self.regs[addr - MREG_NR10] = value;
+                match addr {
+                    MREG_NR10..=MREG_NR14 => self.square1.write(addr - MREG_NR10, value),
+                    MREG_NR20..=MREG_NR24 => self.square2.write(addr - MREG_NR20, value),
+                    MREG_NR30..=MREG_NR34 => self.wave.write(addr - MREG_NR30, value),
+                    MREG_NR40..=MREG_NR44 => self.noise.write(addr - MREG_NR40, value),
+                    _ => (),
+                }
+            },
+            _ => (),
+        }
+    }
+
+    // Powering off clears every register except wave RAM
+    fn set_power(&mut self, on: bool) {
+        if on && !self.powered {
+            self.sequencer_cycles = 0;
+            self.sequencer_step = 0;
+        } else if !on && self.powered {
+            self.square1 = Square::new(true);
+            self.square2 = Square::new(false);
+            self.noise = Noise::new();
+            let ram = self.wave.ram;
+            self.wave = Wave::new();
+            self.wave.ram = ram;
+            self.regs = [0; MREG_NR52 - MREG_NR10];
+        }
+        self.powered = on;
+    }
+
+    fn step_sequencer(&mut self) {
+        if self.sequencer_step & 1 == 0 {
+            self.square1.step_length();
+            self.square2.step_length();
+            self.wave.step_length();
+            self.noise.step_length();
+        }
+        if self.sequencer_step == 2 || self.sequencer_step == 6 {
+            self.square1.step_sweep();
+        }
+        if self.sequencer_step == 7 {
+            self.square1.step_envelope();
+            self.square2.step_envelope();
+            self.noise.step_envelope();
+        }
+        self.sequencer_step = (self.sequencer_step + 1) % 8;
+    }
+
+    // Converts a 4 bit channel output to an analog level, a disabled DAC
+    // outputs silence
+    fn dac(output: u8, enabled: bool) -> f32 {
+        if enabled {
+            output as f32 / 7.5 - 1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn mix(&self) -> (f32, f32) {
+        if !self.powered {
+            return (0.0, 0.0)
+        }
+
+        let channels = [
+            Apu::dac(self.square1.output(), self.square1.dac_enabled()),
+            Apu::dac(self.square2.output(), self.square2.dac_enabled()),
+            Apu::dac(self.wave.output(), self.wave.dac_enabled()),
+            Apu::dac(self.noise.output(), self.noise.dac_enabled()),
+        ];
+
+        // NR51 routes each channel to the left and right outputs, NR50
+        // sets the volume of each side
+        let nr50 = self.regs[MREG_NR50 - MREG_NR10];
+        let nr51 = self.regs[MREG_NR51 - MREG_NR10];
+        let mut left = 0.0;
+        let mut right = 0.0;
+        for (i, level) in channels.iter().enumerate() {
+            if nr51 & (0x10 << i) != 0 {
+                left += level;
+            }
+            if nr51 & (0x01 << i) != 0 {
+                right += level;
+            }
+        }
+        let left_volume = (((nr50 >> 4) & 0b111) + 1) as f32 / 8.0;
+        let right_volume = ((nr50 & 0b111) + 1) as f32 / 8.0;
+        (left / 4.0 * left_volume, right / 4.0 * right_volume)
+    }
+
+    pub fn tick(&mut self, cycles: usize) {
+        for _ in 0..cycles {
+            if self.powered {
+                self.sequencer_cycles += 1;
+                if self.sequencer_cycles == FRAME_SEQUENCER_CYCLES {
+                    self.sequencer_cycles = 0;
+                    self.step_sequencer();
+                }
+                self.square1.tick();
+                self.square2.tick();
+                self.wave.tick();
+                self.noise.tick();
+            }
+
+            self.sample_clock += self.sample_rate;
+            if self.sample_clock >= CLOCK_SPEED {
+                self.sample_clock -= CLOCK_SPEED;
+                // Without anything draining them, at most a second of
+                // samples is kept
+                if self.samples.len() < self.sample_rate * 2 {
+                    let (left, right) = self.mix();
+                    self.samples.push(left);
+                    self.samples.push(right);
+                }
+            }
+        }
+    }
+}
+
+impl Default for Apu {
+    fn default() -> Apu {
+        Apu::new(DEFAULT_SAMPLE_RATE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn powered_apu() -> Apu {
+        let mut apu = Apu::default();
+        apu.write(MREG_NR52, NR52_POWER);
+        apu
+    }
+
+    #[test]
+    fn test_power_gates_writes() {
+        let mut apu = Apu::default();
+        apu.write(MREG_NR12, 0xf0);
+        apu.write(MREG_WAV00, 0x12);
+        assert_eq!(apu.read(MREG_NR12), 0x00);
+        assert_eq!(apu.read(MREG_WAV00), 0x12);
+
+        apu.write(MREG_NR52, NR52_POWER);
+        apu.write(MREG_NR12, 0xf0);
+        assert_eq!(apu.read(MREG_NR12), 0xf0);
+
+        apu.write(MREG_NR52, 0);
+        assert_eq!(apu.read(MREG_NR12), 0x00);
+        assert_eq!(apu.read(MREG_WAV00), 0x12);
+    }
+
+    #[test]
+    fn test_channel_status() {
+        let mut apu = powered_apu();
+        apu.write(MREG_NR22, 0xf0);
+        apu.write(MREG_NR24, 0x80);
+        apu.write(MREG_NR42, 0xf0);
+        apu.write(MREG_NR44, 0x80);
+        assert_eq!(apu.read(MREG_NR52), NR52_POWER | 0b1010);
+    }
+
+    #[test]
+    fn test_length_expires() {
+        let mut apu = powered_apu();
+        apu.write(MREG_NR21, 63);
+        apu.write(MREG_NR22, 0xf0);
+        apu.write(MREG_NR24, 0xc0);
+        assert_eq!(apu.read(MREG_NR52) & 0b10, 0b10);
+
+        // Lengths are clocked on every other step of the sequencer
+        apu.tick(FRAME_SEQUENCER_CYCLES);
+        assert_eq!(apu.read(MREG_NR52) & 0b10, 0);
+    }
+
+    #[test]
+    fn test_sample_rate() {
+        let mut apu = Apu::new(32_768);
+        apu.tick(CLOCK_SPEED / 64);
+        assert_eq!(apu.drain_samples().count(), 2 * 32_768 / 64);
+        assert_eq!(apu.drain_samples().count(), 0);
+    }
+
+    #[test]
+    fn test_mixing() {
+        let mut apu = powered_apu();
+        apu.write(MREG_NR50, 0x07);
+        apu.write(MREG_NR51, 0x01);
+        apu.write(MREG_NR12, 0xf0);
+        apu.write(MREG_NR11, 0b1100_0000);
+        apu.write(MREG_NR13, 0xff);
+        apu.write(MREG_NR14, 0x87);
+
+        // Only routed to the right, where the first step of the duty cycle
+        // is at the lowest level
+        let (left, right) = apu.mix();
+        assert_eq!(left, 0.0);
+        assert_eq!(right, -0.25);
+
+        apu.tick(4);
+        let (_, right) = apu.mix();
+        assert_eq!(right, 0.25);
+    }
+}
@@ -0,0 +1,153 @@
+use apu::envelope::Envelope;
+use apu::length::LengthCounter;
+
+const DIVISORS: [u16; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
+
+// Channel 4, pseudo random noise from a linear feedback shift register
+#[derive(Debug)]
+pub struct Noise {
+    length: LengthCounter,
+    envelope: Envelope,
+    shift: u8,
+    short_mode: bool,
+    divisor: u8,
+    timer: u32,
+    lfsr: u16,
+    pub enabled: bool,
+}
+
+impl Noise {
+    pub fn new() -> Noise {
+        Noise {
+            length: LengthCounter::new(64),
+            envelope: Envelope::default(),
+            shift: 0,
+            short_mode: false,
+            divisor: 0,
+            timer: 0,
+            lfsr: 0x7fff,
+            enabled: false,
+        }
+    }
+
+    pub fn write(&mut self, reg: usize, value: u8) {
+        match reg {
+            0 => (),
+            1 => self.length.load(value),
+            2 => {
+                self.envelope.write(value);
+                if !self.envelope.dac_enabled() {
+                    self.enabled = false;
+                }
+            },
+            3 => {
+                self.shift = value >> 4;
+                self.short_mode = value & 0b1000 != 0;
+                self.divisor = value & 0b111;
+            },
+            _ => {
+                self.length.enabled = value & 0x40 != 0;
+                if value & 0x80 != 0 {
+                    self.trigger();
+                }
+            },
+        }
+    }
+
+    pub fn write_length(&mut self, value: u8) {
+        self.length.load(value);
+    }
+
+    fn period(&self) -> u32 {
+        (DIVISORS[self.divisor as usize] as u32) << self.shift
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+        self.length.trigger();
+        self.envelope.trigger();
+        self.timer = self.period();
+        self.lfsr = 0x7fff;
+    }
+
+    pub fn tick(&mut self) {
+        if self.timer > 1 {
+            self.timer -= 1;
+            return
+        }
+        self.timer = self.period();
+
+        let bit = (self.lfsr & 1) ^ ((self.lfsr >> 1) & 1);
+        self.lfsr = (self.lfsr >> 1) | (bit << 14);
+        // The 7 bit mode also feeds the result back into bit 6
+        if self.short_mode {
+            self.lfsr = (self.lfsr & !0x40) | (bit << 6);
+        }
+    }
+
+    pub fn step_length(&mut self) {
+        if self.length.step() {
+            self.enabled = false;
+        }
+    }
+
+    pub fn step_envelope(&mut self) {
+        self.envelope.step();
+    }
+
+    pub fn dac_enabled(&self) -> bool {
+        self.envelope.dac_enabled()
+    }
+
+    pub fn output(&self) -> u8 {
+        if self.enabled && self.lfsr & 1 == 0 {
+            self.envelope.volume
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lfsr_sequence() {
+        let mut noise = Noise::new();
+        noise.write(2, 0xf0);
+        noise.write(3, 0x00);
+        noise.write(4, 0x80);
+
+        // The register starts out all ones, so the first shifted in bit
+        // is a zero
+        for _ in 0..8 {
+            noise.tick();
+        }
+        assert_eq!(noise.lfsr, 0x3fff);
+        assert_eq!(noise.output(), 0);
+
+        for _ in 0..8 * 13 {
+            noise.tick();
+        }
+        assert_eq!(noise.lfsr, 0x0001);
+
+        for _ in 0..8 {
+            noise.tick();
+        }
+        assert_eq!(noise.lfsr, 0x4000);
+        assert_eq!(noise.output(), 15);
+    }
+
+    #[test]
+    fn test_short_mode() {
+        let mut noise = Noise::new();
+        noise.write(2, 0xf0);
+        noise.write(3, 0b1000);
+        noise.write(4, 0x80);
+        for _ in 0..8 {
+            noise.tick();
+        }
+        assert_eq!(noise.lfsr, 0x3fbf);
+    }
+}
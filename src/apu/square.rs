@@ -0,0 +1,257 @@
+use apu::envelope::Envelope;
+use apu::length::LengthCounter;
+
+const DUTY_PATTERNS: [u8; 4] = [
+    0b0000_0001,    // 12.5 %
+    0b1000_0001,    // 25 %
+    0b1000_0111,    // 50 %
+    0b0111_1110,    // 75 %
+];
+
+const MAX_FREQUENCY: u16 = 2047;
+
+#[derive(Debug, Default)]
+struct Sweep {
+    period: u8,
+    negate: bool,
+    shift: u8,
+    timer: u8,
+    shadow: u16,
+    enabled: bool,
+}
+
+// Square wave channels 1 and 2. Only channel 1 has a frequency sweep.
+#[derive(Debug)]
+pub struct Square {
+    sweep: Option<Sweep>,
+    length: LengthCounter,
+    envelope: Envelope,
+    duty: u8,
+    step: u8,
+    frequency: u16,
+    timer: u16,
+    pub enabled: bool,
+}
+
+impl Square {
+    pub fn new(with_sweep: bool) -> Square {
+        Square {
+            sweep: if with_sweep { Some(Sweep::default()) } else { None },
+            length: LengthCounter::new(64),
+            envelope: Envelope::default(),
+            duty: 0,
+            step: 0,
+            frequency: 0,
+            timer: 0,
+            enabled: false,
+        }
+    }
+
+    // `reg` is the register number, 0 for NRx0 through 4 for NRx4
+    pub fn write(&mut self, reg: usize, value: u8) {
+        match reg {
+            0 => {
+                if let Some(ref mut sweep) = self.sweep {
+                    sweep.period = (value >> 4) & 0b111;
+                    sweep.negate = value & 0b1000 != 0;
+                    sweep.shift = value & 0b111;
+                }
+            },
+            1 => {
+                self.duty = value >> 6;
+                self.length.load(value);
+            },
+            2 => {
+                self.envelope.write(value);
+                if !self.envelope.dac_enabled() {
+                    self.enabled = false;
+                }
+            },
+            3 => self.frequency = (self.frequency & 0x700) | value as u16,
+            _ => {
+                self.frequency = (self.frequency & 0xff) | ((value as u16 & 0b111) << 8);
+                self.length.enabled = value & 0x40 != 0;
+                if value & 0x80 != 0 {
+                    self.trigger();
+                }
+            },
+        }
+    }
+
+    // Only the length can be set while the APU is powered off
+    pub fn write_length(&mut self, value: u8) {
+        self.length.load(value);
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+        self.length.trigger();
+        self.envelope.trigger();
+        self.timer = (2048 - self.frequency) * 4;
+
+        let frequency = self.frequency;
+        let overflow = match self.sweep {
+            Some(ref mut sweep) => {
+                sweep.shadow = frequency;
+                sweep.timer = if sweep.period == 0 { 8 } else { sweep.period };
+                sweep.enabled = sweep.period != 0 || sweep.shift != 0;
+                sweep.shift != 0 && Square::sweep_target(sweep) > MAX_FREQUENCY
+            },
+            None => false,
+        };
+        if overflow {
+            self.enabled = false;
+        }
+    }
+
+    fn sweep_target(sweep: &Sweep) -> u16 {
+        let delta = sweep.shadow >> sweep.shift;
+        if sweep.negate {
+            sweep.shadow.wrapping_sub(delta)
+        } else {
+            sweep.shadow + delta
+        }
+    }
+
+    pub fn tick(&mut self) {
+        if self.timer > 1 {
+            self.timer -= 1;
+            return
+        }
+        self.timer = (2048 - self.frequency) * 4;
+        self.step = (self.step + 1) % 8;
+    }
+
+    pub fn step_length(&mut self) {
+        if self.length.step() {
+            self.enabled = false;
+        }
+    }
+
+    pub fn step_envelope(&mut self) {
+        self.envelope.step();
+    }
+
+    pub fn step_sweep(&mut self) {
+        let sweep = match self.sweep {
+            Some(ref mut sweep) => sweep,
+            None => return,
+        };
+
+        sweep.timer = sweep.timer.saturating_sub(1);
+        if sweep.timer > 0 {
+            return
+        }
+        sweep.timer = if sweep.period == 0 { 8 } else { sweep.period };
+        if !sweep.enabled || sweep.period == 0 {
+            return
+        }
+
+        // The new frequency is checked for overflow twice, once before it
+        // is applied and once more with the next step
+        let target = Square::sweep_target(sweep);
+        if target > MAX_FREQUENCY {
+            self.enabled = false;
+            return
+        }
+        if sweep.shift != 0 {
+            sweep.shadow = target;
+            self.frequency = target;
+            if Square::sweep_target(sweep) > MAX_FREQUENCY {
+                self.enabled = false;
+            }
+        }
+    }
+
+    pub fn dac_enabled(&self) -> bool {
+        self.envelope.dac_enabled()
+    }
+
+    pub fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0
+        }
+        if DUTY_PATTERNS[self.duty as usize] & (1 << self.step) != 0 {
+            self.envelope.volume
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duty_cycle() {
+        let mut square = Square::new(false);
+        square.write(1, 0b1000_0000);
+        square.write(2, 0xf0);
+        square.write(3, 0xff);
+        square.write(4, 0x87);
+
+        // 50 % duty, with a period of 4 cycles per step
+        let mut high = 0;
+        for _ in 0..32 {
+            square.tick();
+            if square.output() == 15 {
+                high += 1;
+            }
+        }
+        assert_eq!(high, 16);
+    }
+
+    #[test]
+    fn test_dac_off_disables() {
+        let mut square = Square::new(false);
+        square.write(2, 0xf0);
+        square.write(4, 0x80);
+        assert!(square.enabled);
+
+        square.write(2, 0x00);
+        assert!(!square.enabled);
+
+        square.write(4, 0x80);
+        assert!(!square.enabled);
+    }
+
+    #[test]
+    fn test_length_counter() {
+        let mut square = Square::new(false);
+        square.write(1, 62);
+        square.write(2, 0xf0);
+        square.write(4, 0xc0);
+        square.step_length();
+        assert!(square.enabled);
+        square.step_length();
+        assert!(!square.enabled);
+    }
+
+    #[test]
+    fn test_sweep_overflow() {
+        let mut square = Square::new(true);
+        square.write(0, 0b0001_0001);
+        square.write(2, 0xf0);
+        square.write(3, 0x00);
+        square.write(4, 0x85);
+        assert!(square.enabled);
+
+        // 0x500 + 0x280 is still in range, but the check of the following
+        // step, 0x780 + 0x3c0, is not
+        square.step_sweep();
+        assert!(!square.enabled);
+    }
+
+    #[test]
+    fn test_sweep_changes_frequency() {
+        let mut square = Square::new(true);
+        square.write(0, 0b0001_1001);
+        square.write(2, 0xf0);
+        square.write(3, 0x00);
+        square.write(4, 0x84);
+        square.step_sweep();
+        assert!(square.enabled);
+        assert_eq!(square.frequency, 0x400 - 0x200);
+    }
+}
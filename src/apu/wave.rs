@@ -0,0 +1,117 @@
+use apu::length::LengthCounter;
+
+// Volume codes shift the 4 bit samples right by this amount
+const VOLUME_SHIFT: [u8; 4] = [4, 0, 1, 2];
+
+// Channel 3, playing back 32 4 bit samples from wave RAM
+#[derive(Debug)]
+pub struct Wave {
+    pub ram: [u8; 16],
+    length: LengthCounter,
+    dac_enabled: bool,
+    volume: u8,
+    frequency: u16,
+    timer: u16,
+    position: usize,
+    pub enabled: bool,
+}
+
+impl Wave {
+    pub fn new() -> Wave {
+        Wave {
+            ram: [0; 16],
+            length: LengthCounter::new(256),
+            dac_enabled: false,
+            volume: 0,
+            frequency: 0,
+            timer: 0,
+            position: 0,
+            enabled: false,
+        }
+    }
+
+    pub fn write(&mut self, reg: usize, value: u8) {
+        match reg {
+            0 => {
+                self.dac_enabled = value & 0x80 != 0;
+                if !self.dac_enabled {
+                    self.enabled = false;
+                }
+            },
+            1 => self.length.load(value),
+            2 => self.volume = (value >> 5) & 0b11,
+            3 => self.frequency = (self.frequency & 0x700) | value as u16,
+            _ => {
+                self.frequency = (self.frequency & 0xff) | ((value as u16 & 0b111) << 8);
+                self.length.enabled = value & 0x40 != 0;
+                if value & 0x80 != 0 {
+                    self.trigger();
+                }
+            },
+        }
+    }
+
+    pub fn write_length(&mut self, value: u8) {
+        self.length.load(value);
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+        self.length.trigger();
+        self.timer = (2048 - self.frequency) * 2;
+        self.position = 0;
+    }
+
+    pub fn tick(&mut self) {
+        if self.timer > 1 {
+            self.timer -= 1;
+            return
+        }
+        self.timer = (2048 - self.frequency) * 2;
+        self.position = (self.position + 1) % 32;
+    }
+
+    pub fn step_length(&mut self) {
+        if self.length.step() {
+            self.enabled = false;
+        }
+    }
+
+    pub fn dac_enabled(&self) -> bool {
+        self.dac_enabled
+    }
+
+    pub fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0
+        }
+        // The high nibble of each byte is played first
+        let byte = self.ram[self.position / 2];
+        let sample = if self.position & 1 == 0 { byte >> 4 } else { byte & 0x0f };
+        sample >> VOLUME_SHIFT[self.volume as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playback() {
+        let mut wave = Wave::new();
+        wave.ram[0] = 0xf3;
+        wave.write(0, 0x80);
+        wave.write(2, 0b0010_0000);
+        wave.write(3, 0xff);
+        wave.write(4, 0x87);
+        assert_eq!(wave.output(), 0x0f);
+
+        wave.tick();
+        wave.tick();
+        assert_eq!(wave.output(), 0x03);
+
+        // Half volume
+        wave.write(2, 0b0100_0000);
+        assert_eq!(wave.output(), 0x01);
+    }
+}
@@ -0,0 +1,17 @@
+mod resampler;
+mod ring_buffer;
+mod wav;
+
+pub use self::resampler::{Resampler, dynamic_ratio};
+pub use self::ring_buffer::RingBuffer;
+pub use self::wav::WavWriter;
+
+// Plays back the interleaved stereo samples produced by the APU
+pub trait AudioSink {
+    fn sample_rate(&self) -> usize;
+
+    fn queue(&mut self, samples: &[f32]);
+
+    // How full the output buffer is, from 0.0 to 1.0
+    fn fill_level(&self) -> f32;
+}
@@ -0,0 +1,92 @@
+// Largest adjustment of the playback rate, small enough to be inaudible
+const MAX_RATE_DELTA: f64 = 0.005;
+
+// Dynamic rate control: slightly stretches the audio while the output
+// buffer is less than half full and squeezes it when it is more, which
+// keeps the buffer from slowly draining or overflowing when the emulated
+// and the real clocks drift apart.
+pub fn dynamic_ratio(fill_level: f32) -> f64 {
+    let fill = (fill_level as f64).clamp(0.0, 1.0);
+    1.0 + MAX_RATE_DELTA * (1.0 - 2.0 * fill)
+}
+
+// Linear interpolating resampler for interleaved stereo samples
+#[derive(Debug, Default)]
+pub struct Resampler {
+    // Position of the next output frame, relative to `last`
+    position: f64,
+    last: (f32, f32),
+}
+
+impl Resampler {
+    pub fn new() -> Resampler {
+        Resampler::default()
+    }
+
+    // Produces `ratio` output frames per input frame
+    pub fn process(&mut self, input: &[f32], ratio: f64, out: &mut Vec<f32>) {
+        let frames = input.len() / 2;
+        if frames == 0 {
+            return
+        }
+
+        // Frame 0 is the last frame of the previous call
+        let frame = |i: usize| if i == 0 {
+            self.last
+        } else {
+            (input[(i - 1) * 2], input[(i - 1) * 2 + 1])
+        };
+
+        let step = 1.0 / ratio;
+        let mut position = self.position;
+        while position < frames as f64 {
+            let i = position as usize;
+            let t = (position - i as f64) as f32;
+            let (l0, r0) = frame(i);
+            let (l1, r1) = frame(i + 1);
+            out.push(l0 + (l1 - l0) * t);
+            out.push(r0 + (r1 - r0) * t);
+            position += step;
+        }
+
+        self.position = position - frames as f64;
+        self.last = (input[frames * 2 - 2], input[frames * 2 - 1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dynamic_ratio() {
+        assert_eq!(dynamic_ratio(0.5), 1.0);
+        assert!(dynamic_ratio(0.1) > 1.0);
+        assert!(dynamic_ratio(0.9) < 1.0);
+        assert_eq!(dynamic_ratio(2.0), 1.0 - MAX_RATE_DELTA);
+    }
+
+    #[test]
+    fn test_unit_ratio() {
+        let mut resampler = Resampler::new();
+        let mut out = Vec::new();
+        resampler.process(&[0.5, -0.5, 1.0, -1.0], 1.0, &mut out);
+        // Output lags one frame behind, starting from silence
+        assert_eq!(out, vec![0.0, 0.0, 0.5, -0.5]);
+
+        out.clear();
+        resampler.process(&[0.0, 0.0], 1.0, &mut out);
+        assert_eq!(out, vec![1.0, -1.0]);
+    }
+
+    #[test]
+    fn test_upsample() {
+        let mut resampler = Resampler::new();
+        let mut out = Vec::new();
+        let input = [1.0; 200];
+        resampler.process(&input, 2.0, &mut out);
+        assert_eq!(out.len(), 400);
+        assert_eq!(out[2], 0.5);
+        assert_eq!(out[399], 1.0);
+    }
+}
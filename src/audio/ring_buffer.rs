@@ -0,0 +1,102 @@
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct Inner {
+    data: Vec<f32>,
+    start: usize,
+    len: usize,
+}
+
+// Fixed size sample queue shared between the emulator and an audio
+// callback running on another thread. Clones refer to the same buffer.
+#[derive(Debug, Clone)]
+pub struct RingBuffer {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl RingBuffer {
+    pub fn new(capacity: usize) -> RingBuffer {
+        RingBuffer {
+            inner: Arc::new(Mutex::new(Inner {
+                data: vec![0.0; capacity],
+                start: 0,
+                len: 0,
+            })),
+        }
+    }
+
+    // Samples that do not fit are dropped. Returns the number queued.
+    pub fn push(&self, samples: &[f32]) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let capacity = inner.data.len();
+        let count = samples.len().min(capacity - inner.len);
+        for &sample in &samples[..count] {
+            let end = (inner.start + inner.len) % capacity;
+            inner.data[end] = sample;
+            inner.len += 1;
+        }
+        count
+    }
+
+    // Fills `out` from the front of the queue, returning how many samples
+    // were available
+    pub fn pop(&self, out: &mut [f32]) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let capacity = inner.data.len();
+        let count = out.len().min(inner.len);
+        for sample in &mut out[..count] {
+            *sample = inner.data[inner.start];
+            inner.start = (inner.start + 1) % capacity;
+            inner.len -= 1;
+        }
+        count
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.inner.lock().unwrap().data.len()
+    }
+
+    pub fn fill_level(&self) -> f32 {
+        let inner = self.inner.lock().unwrap();
+        inner.len as f32 / inner.data.len() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_pop() {
+        let buffer = RingBuffer::new(4);
+        assert_eq!(buffer.push(&[1.0, 2.0, 3.0]), 3);
+        assert_eq!(buffer.fill_level(), 0.75);
+
+        let mut out = [0.0; 2];
+        assert_eq!(buffer.pop(&mut out), 2);
+        assert_eq!(out, [1.0, 2.0]);
+
+        // Wraps around the end of the storage
+        assert_eq!(buffer.push(&[4.0, 5.0, 6.0, 7.0]), 3);
+        let mut out = [0.0; 8];
+        assert_eq!(buffer.pop(&mut out), 4);
+        assert_eq!(&out[..4], &[3.0, 4.0, 5.0, 6.0]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_shared() {
+        let buffer = RingBuffer::new(8);
+        let reader = buffer.clone();
+        buffer.push(&[1.0]);
+        assert_eq!(reader.len(), 1);
+    }
+}
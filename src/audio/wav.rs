@@ -0,0 +1,94 @@
+use errors::*;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const HEADER_BYTES: u32 = 44;
+const CHANNELS: u16 = 2;
+const BITS_PER_SAMPLE: u16 = 16;
+
+// Writes interleaved stereo samples as 16 bit PCM. The sizes in the header
+// are only known once recording stops, so they are filled in by `finish`.
+pub struct WavWriter<W: Write + Seek> {
+    out: W,
+    sample_rate: u32,
+    data_bytes: u32,
+}
+
+impl WavWriter<BufWriter<File>> {
+    pub fn create(path: &Path, sample_rate: usize) -> Result<WavWriter<BufWriter<File>>> {
+        let file = File::create(path)
+            .chain_err(|| format!("Failed to create {}", path.display()))?;
+        WavWriter::new(BufWriter::new(file), sample_rate)
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    pub fn new(out: W, sample_rate: usize) -> Result<WavWriter<W>> {
+        let mut writer = WavWriter {
+            out,
+            sample_rate: sample_rate as u32,
+            data_bytes: 0,
+        };
+        writer.write_header()?;
+        Ok(writer)
+    }
+
+    fn write_header(&mut self) -> Result<()> {
+        let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+        let mut header = Vec::with_capacity(HEADER_BYTES as usize);
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&(HEADER_BYTES - 8 + self.data_bytes).to_le_bytes());
+        header.extend_from_slice(b"WAVEfmt ");
+        header.extend_from_slice(&16u32.to_le_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&CHANNELS.to_le_bytes());
+        header.extend_from_slice(&self.sample_rate.to_le_bytes());
+        header.extend_from_slice(&(self.sample_rate * block_align as u32).to_le_bytes());
+        header.extend_from_slice(&block_align.to_le_bytes());
+        header.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&self.data_bytes.to_le_bytes());
+        self.out.write_all(&header).chain_err(|| "Failed to write WAV header")
+    }
+
+    pub fn write_samples(&mut self, samples: &[f32]) -> Result<()> {
+        let mut data = Vec::with_capacity(samples.len() * 2);
+        for &sample in samples {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        self.out.write_all(&data).chain_err(|| "Failed to write WAV data")?;
+        self.data_bytes += data.len() as u32;
+        Ok(())
+    }
+
+    // Fills in the final sizes and returns the underlying writer
+    pub fn finish(mut self) -> Result<W> {
+        self.out.seek(SeekFrom::Start(0)).chain_err(|| "Failed to seek in WAV file")?;
+        self.write_header()?;
+        self.out.flush().chain_err(|| "Failed to write WAV file")?;
+        Ok(self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_write_wav() {
+        let mut wav = WavWriter::new(Cursor::new(Vec::new()), 44_100).unwrap();
+        wav.write_samples(&[0.0, 1.0, -1.0, 0.5]).unwrap();
+        let data = wav.finish().unwrap().into_inner();
+
+        assert_eq!(data.len(), 44 + 8);
+        assert_eq!(&data[0..4], b"RIFF");
+        assert_eq!(&data[4..8], &44u32.to_le_bytes());
+        assert_eq!(&data[22..24], &2u16.to_le_bytes());
+        assert_eq!(&data[24..28], &44_100u32.to_le_bytes());
+        assert_eq!(&data[40..44], &8u32.to_le_bytes());
+        assert_eq!(&data[44..], &[0x00, 0x00, 0xff, 0x7f, 0x01, 0x80, 0xff, 0x3f]);
+    }
+}
@@ -0,0 +1,133 @@
+use constants::*;
+use errors::*;
+
+// Indexed by the RAM size byte at 0x0149
+const RAM_SIZES: [usize; 6] = [
+    0,
+    0x800,      // 2 kB
+    0x2000,     // 8 kB
+    0x8000,     // 32 kB
+    0x20000,    // 128 kB
+    0x10000,    // 64 kB
+];
+
+#[derive(Debug)]
+pub struct Header {
+    pub title: String,
+    pub cartridge_type: u8,
+    pub rom_size: usize,
+    pub ram_size: usize,
+    pub header_checksum: u8,
+    pub global_checksum: u16,
+    pub header_checksum_valid: bool,
+    pub global_checksum_valid: bool,
+}
+
+impl Header {
+    pub fn parse(rom: &[u8]) -> Result<Header> {
+        if rom.len() <= HEADER_END {
+            bail!("Rom too small to contain a header: {} bytes", rom.len());
+        }
+
+        let title = rom[HEADER_TITLE..HEADER_TITLE_END + 1].iter()
+            .take_while(|&&b| b != 0)
+            .map(|&b| b as char)
+            .collect();
+
+        let rom_code = rom[HEADER_ROM_SIZE];
+        if rom_code > 0x08 {
+            bail!("Unsupported rom size {:02x}", rom_code);
+        }
+
+        let ram_code = rom[HEADER_RAM_SIZE] as usize;
+        let ram_size = *RAM_SIZES.get(ram_code)
+            .chain_err(|| format!("Unsupported ram size {:02x}", ram_code))?;
+
+        let header_checksum = rom[HEADER_CHECKSUM];
+        let global_checksum = ((rom[HEADER_GLOBAL_CHECKSUM] as u16) << 8) |
+            (rom[HEADER_GLOBAL_CHECKSUM + 1] as u16);
+
+        Ok(Header {
+            title,
+            cartridge_type: rom[HEADER_CARTRIDGE_TYPE],
+            rom_size: (ROM_BANK_SIZE * 2) << rom_code,
+            ram_size,
+            header_checksum,
+            global_checksum,
+            header_checksum_valid: Header::compute_header_checksum(rom) == header_checksum,
+            global_checksum_valid: Header::compute_global_checksum(rom) == global_checksum,
+        })
+    }
+
+    pub fn compute_header_checksum(rom: &[u8]) -> u8 {
+        rom[HEADER_TITLE..HEADER_CHECKSUM].iter()
+            .fold(0u8, |acc, &b| acc.wrapping_sub(b).wrapping_sub(1))
+    }
+
+    pub fn compute_global_checksum(rom: &[u8]) -> u16 {
+        rom.iter()
+            .enumerate()
+            .filter(|&(i, _)| i != HEADER_GLOBAL_CHECKSUM && i != HEADER_GLOBAL_CHECKSUM + 1)
+            .fold(0u16, |acc, (_, &b)| acc.wrapping_add(b as u16))
+    }
+
+    pub fn rom_banks(&self) -> usize {
+        self.rom_size / ROM_BANK_SIZE
+    }
+
+    pub fn has_battery(&self) -> bool {
+        matches!(self.cartridge_type,
+                 0x03 | 0x06 | 0x09 | 0x0d | 0x0f | 0x10 | 0x13 | 0x1b | 0x1e | 0x22 | 0xff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_rom() -> Vec<u8> {
+        let mut rom = vec![0u8; 0x8000];
+        rom[HEADER_TITLE..HEADER_TITLE + 6].copy_from_slice(b"TETRIS");
+        rom[HEADER_CARTRIDGE_TYPE] = 0x03;
+        rom[HEADER_ROM_SIZE] = 0x02;
+        rom[HEADER_RAM_SIZE] = 0x03;
+        rom[HEADER_CHECKSUM] = Header::compute_header_checksum(&rom);
+        rom
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let header = Header::parse(&test_rom()).unwrap();
+        assert_eq!(header.title, "TETRIS");
+        assert_eq!(header.cartridge_type, 0x03);
+        assert_eq!(header.rom_size, 0x20000);
+        assert_eq!(header.rom_banks(), 8);
+        assert_eq!(header.ram_size, 0x8000);
+        assert!(header.header_checksum_valid);
+        assert!(header.has_battery());
+    }
+
+    #[test]
+    fn test_header_checksum_mismatch() {
+        let mut rom = test_rom();
+        rom[HEADER_TITLE] = b'X';
+        let header = Header::parse(&rom).unwrap();
+        assert!(!header.header_checksum_valid);
+    }
+
+    #[test]
+    fn test_global_checksum() {
+        let mut rom = test_rom();
+        let sum = Header::compute_global_checksum(&rom);
+        rom[HEADER_GLOBAL_CHECKSUM] = (sum >> 8) as u8;
+        rom[HEADER_GLOBAL_CHECKSUM + 1] = (sum & 0xff) as u8;
+        let header = Header::parse(&rom).unwrap();
+        assert_eq!(header.global_checksum, sum);
+        assert!(header.global_checksum_valid);
+    }
+
+    #[test]
+    fn test_rom_too_small() {
+        assert!(Header::parse(&[0u8; 0x100]).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use constants::*;
+use cartridge::{Mapper, rom_bank_offset};
+
+pub struct Mbc1 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    rom_bank: usize,
+    upper_bank: usize,
+    ram_banking_mode: bool,
+}
+
+impl Mbc1 {
+    pub fn new(rom: Vec<u8>, ram_size: usize) -> Mbc1 {
+        Mbc1 {
+            rom,
+            ram: vec![0u8; ram_size],
+            ram_enabled: false,
+            rom_bank: 1,
+            upper_bank: 0,
+            ram_banking_mode: false,
+        }
+    }
+
+    fn ram_offset(&self, addr: usize) -> usize {
+        // The two bit register only selects RAM bank in advanced banking mode
+        let bank = if self.ram_banking_mode { self.upper_bank } else { 0 };
+        (bank * RAM_BANK_SIZE + (addr - EXT_RAM_START)) % self.ram.len()
+    }
+}
+
+impl Mapper for Mbc1 {
+    fn read_rom(&self, addr: usize) -> u8 {
+        let bank = match addr {
+            0x0000..=0x3fff if self.ram_banking_mode => self.upper_bank << 5,
+            0x0000..=0x3fff => 0,
+            _ => (self.upper_bank << 5) | self.rom_bank,
+        };
+        self.rom[rom_bank_offset(self.rom.len(), bank, addr)]
+    }
+
+    fn write_rom(&mut self, addr: usize, value: u8) {
+        match addr {
+            0x0000..=0x1fff => self.ram_enabled = value & 0x0f == 0x0a,
+            0x2000..=0x3fff => {
+                // Bank 0 can not be selected in the switchable area
+                self.rom_bank = match value & 0x1f {
+                    0 => 1,
+                    b => b as usize,
+                };
+            },
+            0x4000..=0x5fff => self.upper_bank = (value & 0b11) as usize,
+            _ => self.ram_banking_mode = value & 1 == 1,
+        }
+    }
+
+    fn read_ram(&self, addr: usize) -> u8 {
+        if !self.ram_enabled || self.ram.is_empty() {
+            return 0xff
+        }
+        self.ram[self.ram_offset(addr)]
+    }
+
+    fn write_ram(&mut self, addr: usize, value: u8) -> bool {
+        if !self.ram_enabled || self.ram.is_empty() {
+            return false
+        }
+        let offset = self.ram_offset(addr);
+        self.ram[offset] = value;
+        true
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cartridge::banked_rom;
+
+    #[test]
+    fn test_default_banks() {
+        let mbc = Mbc1::new(banked_rom(4), 0);
+        assert_eq!(mbc.read_rom(0x0000), 0);
+        assert_eq!(mbc.read_rom(0x4000), 1);
+    }
+
+    #[test]
+    fn test_switch_rom_bank() {
+        let mut mbc = Mbc1::new(banked_rom(8), 0);
+        mbc.write_rom(0x2000, 5);
+        assert_eq!(mbc.read_rom(0x4000), 5);
+
+        // Writing zero selects bank 1
+        mbc.write_rom(0x2000, 0);
+        assert_eq!(mbc.read_rom(0x4000), 1);
+    }
+
+    #[test]
+    fn test_upper_rom_bank_bits() {
+        let mut mbc = Mbc1::new(banked_rom(128), 0);
+        mbc.write_rom(0x2000, 0x02);
+        mbc.write_rom(0x4000, 0x01);
+        assert_eq!(mbc.read_rom(0x4000), 0x22);
+        assert_eq!(mbc.read_rom(0x0000), 0);
+
+        // Advanced banking mode also maps the upper bits into 0x0000-0x3fff
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.read_rom(0x0000), 0x20);
+    }
+
+    #[test]
+    fn test_bank_wraps_to_rom_size() {
+        let mut mbc = Mbc1::new(banked_rom(4), 0);
+        mbc.write_rom(0x2000, 6);
+        assert_eq!(mbc.read_rom(0x4000), 2);
+    }
+
+    #[test]
+    fn test_ram_disabled_by_default() {
+        let mut mbc = Mbc1::new(banked_rom(2), 0x2000);
+        mbc.write_ram(0xa000, 0x12);
+        assert_eq!(mbc.read_ram(0xa000), 0xff);
+
+        mbc.write_rom(0x0000, 0x0a);
+        mbc.write_ram(0xa000, 0x12);
+        assert_eq!(mbc.read_ram(0xa000), 0x12);
+    }
+
+    #[test]
+    fn test_switch_ram_bank() {
+        let mut mbc = Mbc1::new(banked_rom(2), 0x8000);
+        mbc.write_rom(0x0000, 0x0a);
+        mbc.write_rom(0x6000, 0x01);
+        mbc.write_ram(0xa000, 0x11);
+        mbc.write_rom(0x4000, 0x02);
+        mbc.write_ram(0xa000, 0x22);
+
+        assert_eq!(mbc.read_ram(0xa000), 0x22);
+        mbc.write_rom(0x4000, 0x00);
+        assert_eq!(mbc.read_ram(0xa000), 0x11);
+    }
+}
@@ -0,0 +1,115 @@
+use cartridge::{Mapper, rom_bank_offset};
+
+const MBC2_RAM_SIZE: usize = 512;
+
+pub struct Mbc2 {
+    rom: Vec<u8>,
+    ram: [u8; MBC2_RAM_SIZE],
+    ram_enabled: bool,
+    rom_bank: usize,
+}
+
+impl Mbc2 {
+    pub fn new(rom: Vec<u8>) -> Mbc2 {
+        Mbc2 {
+            rom,
+            ram: [0u8; MBC2_RAM_SIZE],
+            ram_enabled: false,
+            rom_bank: 1,
+        }
+    }
+}
+
+impl Mapper for Mbc2 {
+    fn read_rom(&self, addr: usize) -> u8 {
+        let bank = match addr {
+            0x0000..=0x3fff => 0,
+            _ => self.rom_bank,
+        };
+        self.rom[rom_bank_offset(self.rom.len(), bank, addr)]
+    }
+
+    fn write_rom(&mut self, addr: usize, value: u8) {
+        if addr > 0x3fff {
+            return
+        }
+
+        // Bit 8 of the address selects between RAM enable and ROM bank
+        if addr & 0x100 == 0 {
+            self.ram_enabled = value & 0x0f == 0x0a;
+        } else {
+            self.rom_bank = match value & 0x0f {
+                0 => 1,
+                b => b as usize,
+            };
+        }
+    }
+
+    fn read_ram(&self, addr: usize) -> u8 {
+        if !self.ram_enabled {
+            return 0xff
+        }
+        // Only the lower nibble is stored, the upper bits read as set
+        self.ram[addr & (MBC2_RAM_SIZE - 1)] | 0xf0
+    }
+
+    fn write_ram(&mut self, addr: usize, value: u8) -> bool {
+        if !self.ram_enabled {
+            return false
+        }
+        self.ram[addr & (MBC2_RAM_SIZE - 1)] = value & 0x0f;
+        true
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cartridge::banked_rom;
+
+    #[test]
+    fn test_switch_rom_bank() {
+        let mut mbc = Mbc2::new(banked_rom(16));
+        mbc.write_rom(0x2100, 0x0f);
+        assert_eq!(mbc.read_rom(0x4000), 0x0f);
+
+        // Bit 8 clear does not touch the bank register
+        mbc.write_rom(0x2000, 0x03);
+        assert_eq!(mbc.read_rom(0x4000), 0x0f);
+
+        mbc.write_rom(0x0100, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 0x01);
+    }
+
+    #[test]
+    fn test_ram_nibbles() {
+        let mut mbc = Mbc2::new(banked_rom(2));
+        mbc.write_rom(0x0000, 0x0a);
+        mbc.write_ram(0xa000, 0xab);
+        assert_eq!(mbc.read_ram(0xa000), 0xfb);
+    }
+
+    #[test]
+    fn test_ram_echo() {
+        let mut mbc = Mbc2::new(banked_rom(2));
+        mbc.write_rom(0x0000, 0x0a);
+        mbc.write_ram(0xa1ff, 0x05);
+        assert_eq!(mbc.read_ram(0xa3ff), 0xf5);
+        assert_eq!(mbc.read_ram(0xbfff), 0xf5);
+    }
+
+    #[test]
+    fn test_ram_disabled() {
+        let mut mbc = Mbc2::new(banked_rom(2));
+        mbc.write_ram(0xa000, 0x05);
+        assert_eq!(mbc.read_ram(0xa000), 0xff);
+    }
+}
@@ -0,0 +1,156 @@
+use constants::*;
+use cartridge::{Mapper, rom_bank_offset};
+use cartridge::rtc::Rtc;
+
+pub struct Mbc3 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    rtc: Option<Rtc>,
+    ram_enabled: bool,
+    rom_bank: usize,
+    ram_select: u8,
+}
+
+impl Mbc3 {
+    pub fn new(rom: Vec<u8>, ram_size: usize, rtc: Option<Rtc>) -> Mbc3 {
+        Mbc3 {
+            rom,
+            ram: vec![0u8; ram_size],
+            rtc,
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_select: 0,
+        }
+    }
+
+    fn ram_offset(&self, addr: usize) -> usize {
+        let bank = self.ram_select as usize;
+        (bank * RAM_BANK_SIZE + (addr - EXT_RAM_START)) % self.ram.len()
+    }
+}
+
+impl Mapper for Mbc3 {
+    fn read_rom(&self, addr: usize) -> u8 {
+        let bank = match addr {
+            0x0000..=0x3fff => 0,
+            _ => self.rom_bank,
+        };
+        self.rom[rom_bank_offset(self.rom.len(), bank, addr)]
+    }
+
+    fn write_rom(&mut self, addr: usize, value: u8) {
+        match addr {
+            0x0000..=0x1fff => self.ram_enabled = value & 0x0f == 0x0a,
+            0x2000..=0x3fff => {
+                self.rom_bank = match value & 0x7f {
+                    0 => 1,
+                    b => b as usize,
+                };
+            },
+            0x4000..=0x5fff => self.ram_select = value,
+            _ => {
+                if let Some(ref mut rtc) = self.rtc {
+                    rtc.write_latch(value);
+                }
+            },
+        }
+    }
+
+    fn read_ram(&self, addr: usize) -> u8 {
+        if !self.ram_enabled {
+            return 0xff
+        }
+        match (self.ram_select, self.rtc.as_ref()) {
+            (0x00..=0x03, _) if !self.ram.is_empty() => self.ram[self.ram_offset(addr)],
+            (0x08..=0x0c, Some(rtc)) => rtc.read(self.ram_select),
+            _ => 0xff,
+        }
+    }
+
+    fn write_ram(&mut self, addr: usize, value: u8) -> bool {
+        if !self.ram_enabled {
+            return false
+        }
+        match (self.ram_select, self.rtc.as_mut()) {
+            (0x00..=0x03, _) if !self.ram.is_empty() => {
+                let offset = self.ram_offset(addr);
+                self.ram[offset] = value;
+            },
+            (0x08..=0x0c, Some(rtc)) => rtc.write(self.ram_select, value),
+            _ => return false,
+        }
+        true
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn tick(&mut self, cycles: usize) {
+        if let Some(ref mut rtc) = self.rtc {
+            rtc.tick(cycles);
+        }
+    }
+
+    fn rtc(&mut self) -> Option<&mut Rtc> {
+        self.rtc.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cartridge::banked_rom;
+    use cartridge::rtc::{RtcClock, RTC_SECONDS, RTC_MINUTES};
+
+    #[test]
+    fn test_switch_rom_bank() {
+        let mut mbc = Mbc3::new(banked_rom(128), 0, None);
+        mbc.write_rom(0x2000, 0x7f);
+        assert_eq!(mbc.read_rom(0x4000), 0x7f);
+        mbc.write_rom(0x2000, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 0x01);
+    }
+
+    #[test]
+    fn test_switch_ram_bank() {
+        let mut mbc = Mbc3::new(banked_rom(2), 0x8000, None);
+        mbc.write_rom(0x0000, 0x0a);
+        mbc.write_rom(0x4000, 0x03);
+        mbc.write_ram(0xa010, 0x33);
+        mbc.write_rom(0x4000, 0x00);
+        assert_eq!(mbc.read_ram(0xa010), 0x00);
+        mbc.write_rom(0x4000, 0x03);
+        assert_eq!(mbc.read_ram(0xa010), 0x33);
+    }
+
+    #[test]
+    fn test_rtc_registers() {
+        let mut mbc = Mbc3::new(banked_rom(2), 0x2000, Some(Rtc::new(RtcClock::Cycles)));
+        mbc.write_rom(0x0000, 0x0a);
+        mbc.write_rom(0x4000, RTC_MINUTES);
+        mbc.write_ram(0xa000, 10);
+
+        for _ in 0..3 {
+            mbc.tick(CLOCK_SPEED);
+        }
+        mbc.write_rom(0x6000, 0x00);
+        mbc.write_rom(0x6000, 0x01);
+
+        assert_eq!(mbc.read_ram(0xa000), 10);
+        mbc.write_rom(0x4000, RTC_SECONDS);
+        assert_eq!(mbc.read_ram(0xa000), 3);
+    }
+
+    #[test]
+    fn test_rtc_absent() {
+        let mut mbc = Mbc3::new(banked_rom(2), 0x2000, None);
+        mbc.write_rom(0x0000, 0x0a);
+        mbc.write_rom(0x4000, RTC_SECONDS);
+        assert_eq!(mbc.read_ram(0xa000), 0xff);
+    }
+}
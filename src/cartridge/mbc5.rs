@@ -0,0 +1,144 @@
+use constants::*;
+use cartridge::{Mapper, rom_bank_offset};
+
+const RUMBLE_BIT: u8 = 0b0000_1000;
+
+pub struct Mbc5 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    has_rumble: bool,
+    rumble: bool,
+    ram_enabled: bool,
+    rom_bank: usize,
+    ram_bank: usize,
+}
+
+impl Mbc5 {
+    pub fn new(rom: Vec<u8>, ram_size: usize, has_rumble: bool) -> Mbc5 {
+        Mbc5 {
+            rom,
+            ram: vec![0u8; ram_size],
+            has_rumble,
+            rumble: false,
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+        }
+    }
+
+    fn ram_offset(&self, addr: usize) -> usize {
+        (self.ram_bank * RAM_BANK_SIZE + (addr - EXT_RAM_START)) % self.ram.len()
+    }
+}
+
+impl Mapper for Mbc5 {
+    fn read_rom(&self, addr: usize) -> u8 {
+        let bank = match addr {
+            0x0000..=0x3fff => 0,
+            _ => self.rom_bank,
+        };
+        self.rom[rom_bank_offset(self.rom.len(), bank, addr)]
+    }
+
+    fn write_rom(&mut self, addr: usize, value: u8) {
+        match addr {
+            0x0000..=0x1fff => self.ram_enabled = value & 0x0f == 0x0a,
+            // Unlike MBC1 and MBC3, bank 0 can be mapped to 0x4000-0x7fff
+            0x2000..=0x2fff => self.rom_bank = (self.rom_bank & 0x100) | value as usize,
+            0x3000..=0x3fff => {
+                self.rom_bank = (self.rom_bank & 0xff) | (((value & 1) as usize) << 8);
+            },
+            0x4000..=0x5fff => {
+                if self.has_rumble {
+                    self.rumble = value & RUMBLE_BIT != 0;
+                    self.ram_bank = (value & 0x07) as usize;
+                } else {
+                    self.ram_bank = (value & 0x0f) as usize;
+                }
+            },
+            _ => (),
+        }
+    }
+
+    fn read_ram(&self, addr: usize) -> u8 {
+        if !self.ram_enabled || self.ram.is_empty() {
+            return 0xff
+        }
+        self.ram[self.ram_offset(addr)]
+    }
+
+    fn write_ram(&mut self, addr: usize, value: u8) -> bool {
+        if !self.ram_enabled || self.ram.is_empty() {
+            return false
+        }
+        let offset = self.ram_offset(addr);
+        self.ram[offset] = value;
+        true
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn rumble(&self) -> bool {
+        self.rumble
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cartridge::banked_rom;
+
+    #[test]
+    fn test_nine_bit_rom_bank() {
+        let mut mbc = Mbc5::new(banked_rom(512), 0, false);
+        mbc.write_rom(0x2000, 0x23);
+        mbc.write_rom(0x3000, 0x01);
+        assert_eq!(mbc.read_rom(0x4000), 0x23);
+        assert_eq!(mbc.read_rom(0x4001), 0x01);
+    }
+
+    #[test]
+    fn test_rom_bank_zero() {
+        let mut mbc = Mbc5::new(banked_rom(4), 0, false);
+        mbc.write_rom(0x2000, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 0x00);
+    }
+
+    #[test]
+    fn test_switch_ram_bank() {
+        let mut mbc = Mbc5::new(banked_rom(2), 0x20000, false);
+        mbc.write_rom(0x0000, 0x0a);
+        mbc.write_rom(0x4000, 0x0f);
+        mbc.write_ram(0xa000, 0x0f);
+        mbc.write_rom(0x4000, 0x00);
+        assert_eq!(mbc.read_ram(0xa000), 0x00);
+        mbc.write_rom(0x4000, 0x0f);
+        assert_eq!(mbc.read_ram(0xa000), 0x0f);
+    }
+
+    #[test]
+    fn test_rumble() {
+        let mut mbc = Mbc5::new(banked_rom(2), 0x8000, true);
+        mbc.write_rom(0x0000, 0x0a);
+        mbc.write_rom(0x4000, 0x08 | 0x01);
+        assert!(mbc.rumble());
+        mbc.write_ram(0xa000, 0x11);
+
+        mbc.write_rom(0x4000, 0x01);
+        assert!(!mbc.rumble());
+        assert_eq!(mbc.read_ram(0xa000), 0x11);
+    }
+
+    #[test]
+    fn test_no_rumble_motor() {
+        let mut mbc = Mbc5::new(banked_rom(2), 0x20000, false);
+        mbc.write_rom(0x4000, 0x08);
+        assert!(!mbc.rumble());
+    }
+}
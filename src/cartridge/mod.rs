@@ -0,0 +1,326 @@
+mod header;
+mod mbc1;
+mod mbc2;
+mod mbc3;
+mod mbc5;
+mod rom_only;
+mod rtc;
+
+pub use self::header::Header;
+pub use self::mbc1::Mbc1;
+pub use self::mbc2::Mbc2;
+pub use self::mbc3::Mbc3;
+pub use self::mbc5::Mbc5;
+pub use self::rom_only::RomOnly;
+pub use self::rtc::{Rtc, RtcClock, RTC_SAVE_SIZE};
+
+use constants::*;
+use errors::*;
+use std::cmp;
+use std::fmt;
+use std::fs::File;
+use std::io::{ErrorKind, Read, Write};
+use std::path::Path;
+
+pub trait Mapper {
+    fn read_rom(&self, addr: usize) -> u8;
+    fn write_rom(&mut self, addr: usize, value: u8);
+    fn read_ram(&self, addr: usize) -> u8;
+    // Returns whether the write reached the RAM or a clock register
+    fn write_ram(&mut self, addr: usize, value: u8) -> bool;
+    fn ram(&self) -> &[u8];
+    fn ram_mut(&mut self) -> &mut [u8];
+
+    fn tick(&mut self, _cycles: usize) {}
+
+    fn rumble(&self) -> bool {
+        false
+    }
+
+    fn rtc(&mut self) -> Option<&mut Rtc> {
+        None
+    }
+}
+
+// Offset into a rom image of `addr` within the given 16 kB bank
+pub fn rom_bank_offset(rom_len: usize, bank: usize, addr: usize) -> usize {
+    let banks = rom_len / ROM_BANK_SIZE;
+    (bank % banks) * ROM_BANK_SIZE + (addr & (ROM_BANK_SIZE - 1))
+}
+
+// Rom image with the number of each bank in its first two bytes
+#[cfg(test)]
+pub fn banked_rom(banks: usize) -> Vec<u8> {
+    let mut rom = vec![0u8; banks * ROM_BANK_SIZE];
+    for b in 0..banks {
+        rom[b * ROM_BANK_SIZE] = (b & 0xff) as u8;
+        rom[b * ROM_BANK_SIZE + 1] = (b >> 8) as u8;
+    }
+    rom
+}
+
+pub struct Cartridge {
+    pub header: Header,
+    mapper: Box<dyn Mapper>,
+    ram_dirty: bool,
+    has_rtc: bool,
+}
+
+impl Cartridge {
+    pub fn new(mut rom: Vec<u8>) -> Result<Cartridge> {
+        // Small images (e.g. test programs) are padded to a full 32 kB cart
+        if rom.len() < 2 * ROM_BANK_SIZE {
+            rom.resize(2 * ROM_BANK_SIZE, 0);
+        }
+
+        let header = Header::parse(&rom)?;
+        let rom_size = cmp::max(header.rom_size, rom.len());
+        rom.resize(rom_size, 0);
+
+        let mut mapper: Box<dyn Mapper> = match header.cartridge_type {
+            0x00 | 0x08 | 0x09 => Box::new(RomOnly::new(rom, header.ram_size)),
+            0x01..=0x03 => Box::new(Mbc1::new(rom, header.ram_size)),
+            0x05 | 0x06 => Box::new(Mbc2::new(rom)),
+            0x0f | 0x10 => {
+                let rtc = Rtc::new(RtcClock::Cycles);
+                Box::new(Mbc3::new(rom, header.ram_size, Some(rtc)))
+            },
+            0x11..=0x13 => Box::new(Mbc3::new(rom, header.ram_size, None)),
+            0x19..=0x1b => Box::new(Mbc5::new(rom, header.ram_size, false)),
+            0x1c..=0x1e => Box::new(Mbc5::new(rom, header.ram_size, true)),
+            t => bail!("Unsupported cartridge type {:02x}", t),
+        };
+
+        let has_rtc = mapper.rtc().is_some();
+        Ok(Cartridge {
+            header,
+            mapper,
+            ram_dirty: false,
+            has_rtc,
+        })
+    }
+
+    pub fn read(&self, addr: usize) -> u8 {
+        match addr {
+            0x0000..=ROM_END => self.mapper.read_rom(addr),
+            _ => self.mapper.read_ram(addr),
+        }
+    }
+
+    pub fn write(&mut self, addr: usize, value: u8) {
+        match addr {
+            0x0000..=ROM_END => self.mapper.write_rom(addr, value),
+            // Includes the clock registers of MBC3, which are saved as well
+            _ => {
+                if self.mapper.write_ram(addr, value) {
+                    self.ram_dirty = true;
+                }
+            },
+        }
+    }
+
+    pub fn tick(&mut self, cycles: usize) {
+        self.mapper.tick(cycles);
+    }
+
+    pub fn rumble(&self) -> bool {
+        self.mapper.rumble()
+    }
+
+    pub fn rtc_mut(&mut self) -> Option<&mut Rtc> {
+        self.mapper.rtc()
+    }
+
+    pub fn has_battery(&self) -> bool {
+        self.header.has_battery()
+    }
+
+    pub fn has_rtc(&self) -> bool {
+        self.has_rtc
+    }
+
+    pub fn is_ram_dirty(&self) -> bool {
+        self.ram_dirty
+    }
+
+    // Battery saves are the raw external RAM, with the clock state
+    // appended for MBC3 carts with a timer.
+    pub fn load_save(&mut self, save: &mut dyn Read) -> Result<()> {
+        let mut data = Vec::new();
+        save.read_to_end(&mut data).chain_err(|| "Failed to read save")?;
+
+        let ram_size = {
+            let ram = self.mapper.ram_mut();
+            let len = cmp::min(ram.len(), data.len());
+            ram[..len].copy_from_slice(&data[..len]);
+            ram.len()
+        };
+
+        if data.len() >= ram_size + RTC_SAVE_SIZE {
+            if let Some(rtc) = self.mapper.rtc() {
+                rtc.restore(&data[ram_size..ram_size + RTC_SAVE_SIZE]);
+            }
+        }
+        self.ram_dirty = false;
+        Ok(())
+    }
+
+    pub fn write_save(&mut self, save: &mut dyn Write) -> Result<()> {
+        save.write_all(self.mapper.ram()).chain_err(|| "Failed to write save")?;
+        if let Some(rtc) = self.mapper.rtc() {
+            save.write_all(&rtc.save()).chain_err(|| "Failed to write save")?;
+        }
+        self.ram_dirty = false;
+        Ok(())
+    }
+
+    pub fn load_save_file(&mut self, path: &Path) -> Result<()> {
+        if !self.has_battery() {
+            return Ok(())
+        }
+        match File::open(path) {
+            Ok(mut file) => self.load_save(&mut file),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).chain_err(|| format!("Failed to open {}", path.display())),
+        }
+    }
+
+    pub fn write_save_file(&mut self, path: &Path) -> Result<()> {
+        if !self.has_battery() {
+            return Ok(())
+        }
+        let mut file = File::create(path)
+            .chain_err(|| format!("Failed to create {}", path.display()))?;
+        self.write_save(&mut file)
+    }
+}
+
+impl fmt::Debug for Cartridge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cartridge[{} type={:02x} rom={} ram={}]",
+               self.header.title, self.header.cartridge_type,
+               self.header.rom_size, self.header.ram_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_rom(cartridge_type: u8, rom_code: u8) -> Vec<u8> {
+        let mut rom = banked_rom(2 << rom_code);
+        rom[HEADER_CARTRIDGE_TYPE] = cartridge_type;
+        rom[HEADER_ROM_SIZE] = rom_code;
+        rom[HEADER_RAM_SIZE] = 0x02;
+        rom
+    }
+
+    #[test]
+    fn test_small_rom_is_padded() {
+        let cart = Cartridge::new(vec![0x31, 0xfe, 0xff]).unwrap();
+        assert_eq!(cart.read(0x0001), 0xfe);
+        assert_eq!(cart.read(0x7fff), 0x00);
+    }
+
+    #[test]
+    fn test_mbc1_selected() {
+        let mut cart = Cartridge::new(test_rom(0x01, 0x02)).unwrap();
+        cart.write(0x2000, 0x03);
+        assert_eq!(cart.read(0x4000), 0x03);
+    }
+
+    #[test]
+    fn test_rom_ram_selected() {
+        let mut cart = Cartridge::new(test_rom(0x08, 0x00)).unwrap();
+        cart.write(0xa000, 0x42);
+        assert_eq!(cart.read(0xa000), 0x42);
+    }
+
+    #[test]
+    fn test_mbc2_selected() {
+        let mut cart = Cartridge::new(test_rom(0x05, 0x02)).unwrap();
+        cart.write(0x2100, 0x05);
+        assert_eq!(cart.read(0x4000), 0x05);
+    }
+
+    #[test]
+    fn test_mbc5_selected() {
+        let mut cart = Cartridge::new(test_rom(0x1c, 0x03)).unwrap();
+        cart.write(0x2000, 0x0f);
+        cart.write(0x4000, 0x08);
+        assert_eq!(cart.read(0x4000), 0x0f);
+        assert!(cart.rumble());
+    }
+
+    #[test]
+    fn test_mbc3_timer_selected() {
+        let mut cart = Cartridge::new(test_rom(0x10, 0x00)).unwrap();
+        assert!(cart.rtc_mut().is_some());
+
+        let mut cart = Cartridge::new(test_rom(0x13, 0x00)).unwrap();
+        assert!(cart.rtc_mut().is_none());
+    }
+
+    #[test]
+    fn test_save_roundtrip() {
+        let mut cart = Cartridge::new(test_rom(0x03, 0x00)).unwrap();
+        cart.write(0x0000, 0x0a);
+        cart.write(0xa123, 0x55);
+        assert!(cart.is_ram_dirty());
+
+        let mut save = Vec::new();
+        cart.write_save(&mut save).unwrap();
+        assert_eq!(save.len(), 0x2000);
+        assert_eq!(save[0x123], 0x55);
+        assert!(!cart.is_ram_dirty());
+
+        let mut restored = Cartridge::new(test_rom(0x03, 0x00)).unwrap();
+        restored.load_save(&mut &save[..]).unwrap();
+        restored.write(0x0000, 0x0a);
+        assert_eq!(restored.read(0xa123), 0x55);
+    }
+
+    #[test]
+    fn test_save_with_rtc() {
+        let mut cart = Cartridge::new(test_rom(0x10, 0x00)).unwrap();
+        cart.tick(CLOCK_SPEED * 7);
+
+        let mut save = Vec::new();
+        cart.write_save(&mut save).unwrap();
+        assert_eq!(save.len(), 0x2000 + RTC_SAVE_SIZE);
+
+        let mut restored = Cartridge::new(test_rom(0x10, 0x00)).unwrap();
+        restored.load_save(&mut &save[..]).unwrap();
+        restored.write(0x0000, 0x0a);
+        restored.write(0x6000, 0x00);
+        restored.write(0x6000, 0x01);
+        restored.write(0x4000, 0x08);
+        assert_eq!(restored.read(0xa000), 7);
+    }
+
+    #[test]
+    fn test_disabled_ram_write_is_clean() {
+        let mut cart = Cartridge::new(test_rom(0x03, 0x00)).unwrap();
+        cart.write(0xa000, 0x12);
+        assert!(!cart.is_ram_dirty());
+        cart.write(0x0000, 0x0a);
+        cart.write(0xa000, 0x12);
+        assert!(cart.is_ram_dirty());
+    }
+
+    #[test]
+    fn test_rtc_write_is_dirty() {
+        let mut cart = Cartridge::new(test_rom(0x10, 0x00)).unwrap();
+        assert!(cart.has_rtc());
+        cart.write(0x0000, 0x0a);
+        cart.write(0x4000, 0x08);
+        assert!(!cart.is_ram_dirty());
+        cart.write(0xa000, 0x12);
+        assert!(cart.is_ram_dirty());
+    }
+
+    #[test]
+    fn test_unsupported_type() {
+        assert!(Cartridge::new(test_rom(0xfc, 0x00)).is_err());
+    }
+}
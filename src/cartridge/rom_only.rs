@@ -0,0 +1,71 @@
+use constants::*;
+use cartridge::Mapper;
+
+// Carts without a memory bank controller, optionally with up to 8 kB of RAM
+pub struct RomOnly {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+}
+
+impl RomOnly {
+    pub fn new(rom: Vec<u8>, ram_size: usize) -> RomOnly {
+        RomOnly {
+            rom,
+            ram: vec![0u8; ram_size],
+        }
+    }
+}
+
+impl Mapper for RomOnly {
+    fn read_rom(&self, addr: usize) -> u8 {
+        self.rom[addr & ROM_END]
+    }
+
+    fn write_rom(&mut self, _addr: usize, _value: u8) {}
+
+    fn read_ram(&self, addr: usize) -> u8 {
+        if self.ram.is_empty() {
+            return 0xff
+        }
+        self.ram[(addr - EXT_RAM_START) % self.ram.len()]
+    }
+
+    fn write_ram(&mut self, addr: usize, value: u8) -> bool {
+        if self.ram.is_empty() {
+            return false
+        }
+        let offset = (addr - EXT_RAM_START) % self.ram.len();
+        self.ram[offset] = value;
+        true
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rom_is_read_only() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x1234] = 0xab;
+        let mut mapper = RomOnly::new(rom, 0);
+        mapper.write_rom(0x1234, 0xcd);
+        assert_eq!(mapper.read_rom(0x1234), 0xab);
+        assert_eq!(mapper.read_ram(EXT_RAM_START), 0xff);
+    }
+
+    #[test]
+    fn test_rom_with_ram() {
+        let mut mapper = RomOnly::new(vec![0u8; 0x8000], 0x2000);
+        mapper.write_ram(0xbfff, 0x12);
+        assert_eq!(mapper.read_ram(0xbfff), 0x12);
+    }
+}
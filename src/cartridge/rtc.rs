@@ -0,0 +1,299 @@
+use constants::*;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const RTC_SECONDS: u8 = 0x08;
+pub const RTC_MINUTES: u8 = 0x09;
+pub const RTC_HOURS: u8 = 0x0a;
+pub const RTC_DAY_LOW: u8 = 0x0b;
+pub const RTC_DAY_HIGH: u8 = 0x0c;
+
+const DAY_HIGH_BIT: u8 = 0b0000_0001;
+const HALT_BIT: u8 = 0b0100_0000;
+const DAY_CARRY_BIT: u8 = 0b1000_0000;
+
+// Size of the clock state appended to battery saves by other emulators
+pub const RTC_SAVE_SIZE: usize = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RtcClock {
+    // Advanced by emulated CPU cycles, so runs are deterministic
+    Cycles,
+    // Follows the host's wall clock
+    WallTime,
+}
+
+pub struct Rtc {
+    clock: RtcClock,
+    seconds: u8,
+    minutes: u8,
+    hours: u8,
+    days: u16,
+    halted: bool,
+    day_carry: bool,
+    latched: [u8; 5],
+    latch_armed: bool,
+    cycles: usize,
+    last_sync: SystemTime,
+}
+
+impl Rtc {
+    pub fn new(clock: RtcClock) -> Rtc {
+        Rtc {
+            clock,
+            seconds: 0,
+            minutes: 0,
+            hours: 0,
+            days: 0,
+            halted: false,
+            day_carry: false,
+            latched: [0; 5],
+            latch_armed: false,
+            cycles: 0,
+            last_sync: SystemTime::now(),
+        }
+    }
+
+    pub fn set_clock(&mut self, clock: RtcClock) {
+        self.sync();
+        self.clock = clock;
+        self.cycles = 0;
+        self.last_sync = SystemTime::now();
+    }
+
+    pub fn tick(&mut self, cycles: usize) {
+        if self.clock != RtcClock::Cycles || self.halted {
+            return
+        }
+        self.cycles += cycles;
+        if self.cycles >= CLOCK_SPEED {
+            let seconds = self.cycles / CLOCK_SPEED;
+            self.cycles %= CLOCK_SPEED;
+            self.advance(seconds as u64);
+        }
+    }
+
+    fn sync(&mut self) {
+        if self.clock != RtcClock::WallTime {
+            return
+        }
+        let elapsed = self.last_sync.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+        if elapsed == 0 {
+            return
+        }
+        // Fractions of a second are left for the next sync
+        self.last_sync += Duration::from_secs(elapsed);
+        if !self.halted {
+            self.advance(elapsed);
+        }
+    }
+
+    pub fn advance(&mut self, seconds: u64) {
+        let total = self.seconds as u64 + seconds;
+        self.seconds = (total % 60) as u8;
+
+        let total = self.minutes as u64 + total / 60;
+        self.minutes = (total % 60) as u8;
+
+        let total = self.hours as u64 + total / 60;
+        self.hours = (total % 24) as u8;
+
+        let total = self.days as u64 + total / 24;
+        if total > 0x1ff {
+            self.day_carry = true;
+        }
+        self.days = (total % 0x200) as u16;
+    }
+
+    fn registers(&self) -> [u8; 5] {
+        let mut day_high = ((self.days >> 8) as u8) & DAY_HIGH_BIT;
+        if self.halted {
+            day_high |= HALT_BIT;
+        }
+        if self.day_carry {
+            day_high |= DAY_CARRY_BIT;
+        }
+        [self.seconds, self.minutes, self.hours, self.days as u8, day_high]
+    }
+
+    // Same layout as BGB and VBA-M: live and latched registers as 32 bit
+    // little endian words, followed by a 64 bit unix timestamp.
+    pub fn save(&mut self) -> [u8; RTC_SAVE_SIZE] {
+        self.sync();
+        let mut data = [0u8; RTC_SAVE_SIZE];
+        let live = self.registers();
+        for (i, &reg) in live.iter().chain(self.latched.iter()).enumerate() {
+            data[i * 4] = reg;
+        }
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        for i in 0..8 {
+            data[40 + i] = (timestamp >> (i * 8)) as u8;
+        }
+        data
+    }
+
+    pub fn restore(&mut self, data: &[u8]) {
+        if data.len() < RTC_SAVE_SIZE {
+            return
+        }
+
+        for i in 0..5 {
+            self.write(RTC_SECONDS + i as u8, data[i * 4]);
+            self.latched[i] = data[20 + i * 4];
+        }
+
+        // Catch up with the time passed since the save was written
+        if self.clock == RtcClock::WallTime {
+            let timestamp = (0..8).fold(0u64, |acc, i| acc | (data[40 + i] as u64) << (i * 8));
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(timestamp);
+            if !self.halted && now > timestamp {
+                self.advance(now - timestamp);
+            }
+            self.last_sync = SystemTime::now();
+        }
+    }
+
+    // Writing 0x00 followed by 0x01 copies the running clock into the
+    // readable registers.
+    pub fn write_latch(&mut self, value: u8) {
+        if self.latch_armed && value == 0x01 {
+            self.sync();
+            self.latched = self.registers();
+        }
+        self.latch_armed = value == 0x00;
+    }
+
+    pub fn read(&self, reg: u8) -> u8 {
+        match reg {
+            RTC_SECONDS..=RTC_DAY_HIGH => self.latched[(reg - RTC_SECONDS) as usize],
+            _ => 0xff,
+        }
+    }
+
+    pub fn write(&mut self, reg: u8, value: u8) {
+        self.sync();
+        match reg {
+            RTC_SECONDS => {
+                self.seconds = value & 0x3f;
+                self.cycles = 0;
+            },
+            RTC_MINUTES => self.minutes = value & 0x3f,
+            RTC_HOURS => self.hours = value & 0x1f,
+            RTC_DAY_LOW => self.days = (self.days & 0x100) | value as u16,
+            RTC_DAY_HIGH => {
+                self.days = (self.days & 0xff) | (((value & DAY_HIGH_BIT) as u16) << 8);
+                self.halted = value & HALT_BIT != 0;
+                self.day_carry = value & DAY_CARRY_BIT != 0;
+            },
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latch(rtc: &mut Rtc) {
+        rtc.write_latch(0x00);
+        rtc.write_latch(0x01);
+    }
+
+    #[test]
+    fn test_tick_cycles() {
+        let mut rtc = Rtc::new(RtcClock::Cycles);
+        rtc.tick(CLOCK_SPEED - 4);
+        latch(&mut rtc);
+        assert_eq!(rtc.read(RTC_SECONDS), 0);
+
+        rtc.tick(4);
+        rtc.tick(CLOCK_SPEED * 60);
+        latch(&mut rtc);
+        assert_eq!(rtc.read(RTC_SECONDS), 1);
+        assert_eq!(rtc.read(RTC_MINUTES), 1);
+    }
+
+    #[test]
+    fn test_wall_time_keeps_fraction() {
+        let mut rtc = Rtc::new(RtcClock::WallTime);
+        rtc.last_sync = SystemTime::now() - Duration::from_millis(2500);
+        rtc.sync();
+        assert_eq!(rtc.seconds, 2);
+        assert!(rtc.last_sync.elapsed().unwrap() >= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_latch_holds_value() {
+        let mut rtc = Rtc::new(RtcClock::Cycles);
+        rtc.advance(5);
+        latch(&mut rtc);
+        rtc.advance(5);
+        assert_eq!(rtc.read(RTC_SECONDS), 5);
+
+        // A single write of 0x01 does not latch
+        rtc.write_latch(0x01);
+        assert_eq!(rtc.read(RTC_SECONDS), 5);
+
+        latch(&mut rtc);
+        assert_eq!(rtc.read(RTC_SECONDS), 10);
+    }
+
+    #[test]
+    fn test_day_counter_overflow() {
+        let mut rtc = Rtc::new(RtcClock::Cycles);
+        rtc.advance(511 * 86400);
+        latch(&mut rtc);
+        assert_eq!(rtc.read(RTC_DAY_LOW), 0xff);
+        assert_eq!(rtc.read(RTC_DAY_HIGH), DAY_HIGH_BIT);
+
+        rtc.advance(86400);
+        latch(&mut rtc);
+        assert_eq!(rtc.read(RTC_DAY_LOW), 0x00);
+        assert_eq!(rtc.read(RTC_DAY_HIGH), DAY_CARRY_BIT);
+    }
+
+    #[test]
+    fn test_halt_stops_clock() {
+        let mut rtc = Rtc::new(RtcClock::Cycles);
+        rtc.write(RTC_DAY_HIGH, HALT_BIT);
+        rtc.tick(CLOCK_SPEED * 2);
+        latch(&mut rtc);
+        assert_eq!(rtc.read(RTC_SECONDS), 0);
+        assert_eq!(rtc.read(RTC_DAY_HIGH), HALT_BIT);
+    }
+
+    #[test]
+    fn test_save_restore() {
+        let mut rtc = Rtc::new(RtcClock::Cycles);
+        rtc.advance(3 * 86400 + 3661);
+        latch(&mut rtc);
+        rtc.advance(1);
+        let data = rtc.save();
+        assert_eq!(data[0], 2);
+        assert_eq!(data[20], 1);
+
+        let mut restored = Rtc::new(RtcClock::Cycles);
+        restored.restore(&data);
+        assert_eq!(restored.read(RTC_SECONDS), 1);
+        assert_eq!(restored.read(RTC_DAY_LOW), 3);
+        latch(&mut restored);
+        assert_eq!(restored.read(RTC_SECONDS), 2);
+        assert_eq!(restored.read(RTC_HOURS), 1);
+    }
+
+    #[test]
+    fn test_write_registers() {
+        let mut rtc = Rtc::new(RtcClock::Cycles);
+        rtc.write(RTC_HOURS, 23);
+        rtc.write(RTC_MINUTES, 59);
+        rtc.write(RTC_SECONDS, 59);
+        rtc.advance(1);
+        latch(&mut rtc);
+        assert_eq!(rtc.read(RTC_HOURS), 0);
+        assert_eq!(rtc.read(RTC_DAY_LOW), 1);
+    }
+}
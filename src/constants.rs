@@ -121,5 +121,68 @@ pub const LCD_MODE0_CYCLES: usize = 204;
 pub const LCD_MODE1_CYCLES: usize = 4560;
 pub const LCD_MODE2_CYCLES: usize = 80;
 pub const LCD_MODE3_CYCLES: usize = 172;
+pub const LCD_LINE_CYCLES: usize = 456;
 
 pub const LY_MAX: usize = 154;
+
+pub const STAT_MODE: u8 = 0b0000_0011;
+pub const STAT_COINCIDENCE: u8 = 0b0000_0100;
+pub const STAT_MODE0_INTERRUPT: u8 = 0b0000_1000;
+pub const STAT_MODE1_INTERRUPT: u8 = 0b0001_0000;
+pub const STAT_MODE2_INTERRUPT: u8 = 0b0010_0000;
+pub const STAT_LYC_INTERRUPT: u8 = 0b0100_0000;
+
+pub const ROM_BANK_SIZE: usize = 0x4000;  // 16 kB
+pub const RAM_BANK_SIZE: usize = 0x2000;  // 8 kB
+pub const ROM_END: usize = 0x7fff;
+pub const EXT_RAM_START: usize = 0xa000;
+pub const EXT_RAM_END: usize = 0xbfff;
+
+pub const HEADER_TITLE: usize = 0x0134;
+pub const HEADER_TITLE_END: usize = 0x0143;
+pub const HEADER_CARTRIDGE_TYPE: usize = 0x0147;
+pub const HEADER_ROM_SIZE: usize = 0x0148;
+pub const HEADER_RAM_SIZE: usize = 0x0149;
+pub const HEADER_CHECKSUM: usize = 0x014d;
+pub const HEADER_GLOBAL_CHECKSUM: usize = 0x014e;
+pub const HEADER_END: usize = 0x014f;
+
+pub const BOOT_ROM_END: usize = 0x00ff;
+pub const VRAM_START: usize = 0x8000;
+pub const VRAM_END: usize = 0x9fff;
+pub const WRAM_START: usize = 0xc000;
+pub const WRAM_END: usize = 0xdfff;
+pub const ECHO_START: usize = 0xe000;
+pub const ECHO_END: usize = 0xfdff;
+pub const OAM_START: usize = 0xfe00;
+pub const OAM_END: usize = 0xfe9f;
+pub const UNUSABLE_START: usize = 0xfea0;
+pub const UNUSABLE_END: usize = 0xfeff;
+pub const IO_START: usize = 0xff00;
+pub const IO_END: usize = 0xff7f;
+pub const HRAM_START: usize = 0xff80;
+pub const HRAM_END: usize = 0xfffe;
+
+pub const LCDC_BG_ENABLE: u8 = 0b0000_0001;
+pub const LCDC_OBJ_ENABLE: u8 = 0b0000_0010;
+pub const LCDC_OBJ_SIZE: u8 = 0b0000_0100;
+pub const LCDC_BG_MAP: u8 = 0b0000_1000;
+pub const LCDC_TILE_DATA: u8 = 0b0001_0000;
+pub const LCDC_WINDOW_ENABLE: u8 = 0b0010_0000;
+pub const LCDC_WINDOW_MAP: u8 = 0b0100_0000;
+pub const LCDC_ENABLE: u8 = 0b1000_0000;
+
+pub const TILE_MAP_0: usize = 0x9800;
+pub const TILE_MAP_1: usize = 0x9c00;
+pub const TILE_DATA_UNSIGNED: usize = 0x8000;
+pub const TILE_DATA_SIGNED: usize = 0x9000;
+pub const TILE_BYTES: usize = 16;
+pub const TILE_MAP_WIDTH: usize = 32;
+
+pub const OAM_SPRITES: usize = 40;
+pub const OAM_ENTRY_BYTES: usize = 4;
+pub const SPRITES_PER_LINE: usize = 10;
+pub const OBJ_PALETTE: u8 = 0b0001_0000;
+pub const OBJ_FLIP_X: u8 = 0b0010_0000;
+pub const OBJ_FLIP_Y: u8 = 0b0100_0000;
+pub const OBJ_BEHIND_BG: u8 = 0b1000_0000;
@@ -9,7 +9,21 @@ use operations::*;
 use instruction_set::get_definition;
 use std::fmt;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+
+// Advances the rest of the system by the given number of cycles
+pub type TickHook = Box<dyn FnMut(usize)>;
+
+// LD B,B does nothing, so test roms use it as a software breakpoint
+const BREAKPOINT_OPCODE: u16 = 0x40;
+
+// Each memory access takes one machine cycle
+const ACCESS_CYCLES: usize = 4;
+
+// Two wait states, pushing PC and jumping to the handler
+const INTERRUPT_DISPATCH_CYCLES: usize = 20;
+// Time passing per step while halted or stopped
+const IDLE_CYCLES: usize = 4;
 
 #[derive(Debug, PartialEq)]
 pub enum CPUState {
@@ -26,6 +40,17 @@ pub struct CPU {
     pub mem: Rc<RefCell<Memory>>,
     pub cycles: usize,
     pub state: CPUState,
+    // Interrupt master enable
+    pub ime: bool,
+    // Instructions left until a pending EI takes effect
+    ime_delay: usize,
+    // Set when the byte after HALT is to be read twice
+    halt_bug: bool,
+    // Without a hook, the caller advances the rest of the system after
+    // every step instead of on every memory access
+    tick_hook: RefCell<Option<TickHook>>,
+    ticked: Cell<usize>,
+    breakpoint: bool,
 }
 
 impl fmt::Debug for CPU {
@@ -51,10 +76,30 @@ impl CPU {
             mem,
             cycles: 0,
             state: CPUState::Running,
+            ime: false,
+            ime_delay: 0,
+            halt_bug: false,
+            tick_hook: RefCell::new(None),
+            ticked: Cell::new(0),
+            breakpoint: false,
         }
     }
 
-    pub fn execute(&mut self, instruction: &Instruction) -> Result<()> {
+    // Register contents after the DMG boot rom hands over to the cartridge
+    pub fn init_post_boot(&mut self) {
+        self.store_reg_short(REG_A, REG_F, 0x01b0);
+        self.store_reg_short(REG_B, REG_C, 0x0013);
+        self.store_reg_short(REG_D, REG_E, 0x00d8);
+        self.store_reg_short(REG_H, REG_L, 0x014d);
+        self.sp = 0xfffe;
+        self.pc = 0x0100;
+    }
+
+    // Returns whether a conditional branch was taken
+    pub fn execute(&mut self, instruction: &Instruction) -> Result<bool> {
+        if instruction.definition.code == BREAKPOINT_OPCODE {
+            self.breakpoint = true;
+        }
         let res = match instruction.definition.mnemonic {
             Mnemonic::ADC => AddCarry::execute(instruction, self),
             Mnemonic::ADD => Add::execute(instruction, self),
@@ -102,35 +147,77 @@ impl CPU {
             Mnemonic::SUB => Subtract::execute(instruction, self),
             Mnemonic::SWAP => Swap::execute(instruction, self),
             Mnemonic::XOR => Xor::execute(instruction, self),
-            _ => Ok(())
+            // Opcodes missing from the instruction set do nothing
+            Mnemonic::INVALID => Ok(true),
         };
-        // NOTE: When other cycle count?
-        let instruction_cycles = instruction.definition.cycles[0];
-        self.cycles += instruction_cycles;
-        self.execute_interrupts();
+        let taken = *res.as_ref().unwrap_or(&true);
+        self.cycles += instruction.definition.cycles[if taken { 0 } else { 1 }];
         self.pc = self.pc.wrapping_add(instruction.definition.length as u16);
+
+        if self.ime_delay > 0 {
+            self.ime_delay -= 1;
+            if self.ime_delay == 0 {
+                self.ime = true;
+            }
+        }
         res
     }
 
+    // Returns whether a breakpoint was executed since the last call
+    pub fn take_breakpoint(&mut self) -> bool {
+        let breakpoint = self.breakpoint;
+        self.breakpoint = false;
+        breakpoint
+    }
+
+    pub fn set_tick_hook(&mut self, hook: Option<TickHook>) {
+        *self.tick_hook.borrow_mut() = hook;
+    }
+
+    fn tick(&self, cycles: usize) {
+        if cycles == 0 {
+            return
+        }
+        if let Some(ref mut hook) = *self.tick_hook.borrow_mut() {
+            hook(cycles);
+            self.ticked.set(self.ticked.get() + cycles);
+        }
+    }
+
+    // Accesses happen at the end of their machine cycle, so they see the
+    // state the rest of the system is in after it
     pub fn store_mem(&self, addr: usize, value: u8) {
+        self.tick(ACCESS_CYCLES);
         self.mem.borrow_mut().store(addr, value);
     }
 
     pub fn load_mem(&self, addr: usize) -> u8 {
+        self.tick(ACCESS_CYCLES);
         self.mem.borrow_mut().load(addr)
     }
 
+    // A machine cycle spent without accessing memory
+    pub fn internal_cycle(&self) {
+        self.tick(ACCESS_CYCLES);
+    }
+
     pub fn read_reg_addr(&self, h: usize, l: usize) -> usize {
         ((self.reg[h] as usize) << 8) | (self.reg[l] as usize)
     }
 
+    // F is kept in `flag`, where only the upper four bits exist
     pub fn read_reg_short(&self, h: usize, l: usize) -> u16 {
-        ((self.reg[h] as u16) << 8) | (self.reg[l] as u16)
+        let lo = if l == REG_F { self.flag } else { self.reg[l] };
+        ((self.reg[h] as u16) << 8) | (lo as u16)
     }
 
     pub fn store_reg_short(&mut self, h: usize, l: usize, val: u16) {
         self.reg[h] = (val >> 8) as u8;
         self.reg[l] = (val & 0xff) as u8;
+        if l == REG_F {
+            self.reg[l] &= 0xf0;
+            self.flag = self.reg[l];
+        }
     }
 
     pub fn flag_is_set(&self, flag: u8) -> bool {
@@ -178,16 +265,58 @@ impl CPU {
         }
     }
 
+    // H and C of `a + b + carry`
+    pub fn set_add_carries(&mut self, a: u8, b: u8, carry: u8) {
+        self.flag_cond(FLAG_H, (a & 0xf) + (b & 0xf) + carry > 0xf);
+        self.flag_cond(FLAG_C, a as u16 + b as u16 + carry as u16 > 0xff);
+    }
+
+    // H and C of `a - b - borrow`, set when borrowing from bit 4 and bit 8
+    pub fn set_sub_borrows(&mut self, a: u8, b: u8, borrow: u8) {
+        self.flag_cond(FLAG_H, a & 0xf < (b & 0xf) + borrow);
+        self.flag_cond(FLAG_C, (a as u16) < b as u16 + borrow as u16);
+    }
+
     pub fn set_state(&mut self, state: CPUState) {
         self.state = state;
     }
 
+    // With IME off and an interrupt already pending, HALT does not halt.
+    // Instead the DMG fails to increment PC after the next opcode fetch.
+    // Right after EI the interrupt is taken at once, returning to the HALT.
+    pub fn halt(&mut self) {
+        if !self.ime && self.pending_interrupts() != 0 {
+            if self.ime_delay > 0 {
+                self.pc = self.pc.wrapping_sub(1);
+            } else {
+                self.halt_bug = true;
+            }
+        } else {
+            self.set_state(CPUState::Halted);
+        }
+    }
+
+    // STOP resets the divider and sleeps until a button is pressed
+    pub fn stop(&mut self) {
+        self.mem.borrow_mut().reset_divider();
+        self.set_state(CPUState::Stopped);
+    }
+
     pub fn enable_interrupts(&mut self) {
-        self.mem.borrow_mut().set_interrupts_enabled(true);
+        self.ime = true;
+        self.ime_delay = 0;
+    }
+
+    // EI only takes effect after the instruction following it
+    pub fn enable_interrupts_delayed(&mut self) {
+        if !self.ime {
+            self.ime_delay = 2;
+        }
     }
 
     pub fn disable_interrupts(&mut self) {
-        self.mem.borrow_mut().set_interrupts_enabled(false);
+        self.ime = false;
+        self.ime_delay = 0;
     }
 
     pub fn stack_push(&mut self, b: u8) {
@@ -201,8 +330,22 @@ impl CPU {
         val
     }
 
+    // 16 bit values are stored little endian, so the high byte is pushed
+    // first and popped last
+    pub fn stack_push_u16(&mut self, value: u16) {
+        self.stack_push((value >> 8) as u8);
+        self.stack_push((value & 0xff) as u8);
+    }
+
+    pub fn stack_pop_u16(&mut self) -> u16 {
+        let lo = self.stack_pop() as u16;
+        let hi = self.stack_pop() as u16;
+        (hi << 8) | lo
+    }
+
+    // Decoding only peeks at memory, the fetch is timed by execute_next
     fn next_instruction_byte(&self, offset: &mut usize) -> u8 {
-        let b = self.load_mem((self.pc as usize) + *offset);
+        let b = self.mem.borrow().load((self.pc as usize) + *offset);
         *offset += 1;
         b
     }
@@ -210,6 +353,9 @@ impl CPU {
     pub fn current_instruction(&self) -> Result<Instruction> {
         let mut offset: usize = 0;
         let first = self.next_instruction_byte(&mut offset) as u16;
+        if self.halt_bug {
+            offset = 0;
+        }
         let opcode = match first {
             0xcb => {
                 let second = self.next_instruction_byte(&mut offset) as u16;
@@ -235,29 +381,95 @@ impl CPU {
         })
     }
 
-    pub fn execute_next(&mut self) -> Instruction {
-        let instruction = self.current_instruction().unwrap();
-        self.execute(&instruction);
-        instruction
+    pub fn execute_next(&mut self) -> Result<Instruction> {
+        let start = self.cycles;
+        let ticked = self.ticked.get();
+        let instruction = self.current_instruction()?;
+        // STOP is followed by a byte that is skipped without being fetched
+        let fetches = match instruction.definition.mnemonic {
+            Mnemonic::STOP => 1,
+            _ => instruction.definition.length,
+        };
+        for _ in 0..fetches {
+            self.tick(ACCESS_CYCLES);
+        }
+        if self.halt_bug {
+            // The opcode was read without moving past it
+            self.halt_bug = false;
+            self.pc = self.pc.wrapping_sub(1);
+        }
+        let pc = self.pc;
+        self.execute(&instruction)
+            .chain_err(|| format!("Failed to execute {} at ${:04x}", instruction, pc))?;
+        self.settle_cycles(start, ticked);
+        self.execute_interrupts();
+        Ok(instruction)
     }
 
-    fn execute_interrupts(&mut self) {
-        // Iterate all interrupt flags
-        for interrupt in &INTERRUPTS {
-            let requested = self.load_mem(MREG_IF) & interrupt.flag != 0;
-            let enabled = self.load_mem(MREG_IE) & interrupt.flag != 0;
+    // Ticks the internal delays following the last memory access, so that
+    // the time passed since `start` matches the cycles spent
+    fn settle_cycles(&self, start: usize, ticked: usize) {
+        let spent = self.cycles - start;
+        let ticked = self.ticked.get() - ticked;
+        debug_assert!(ticked <= spent, "ticked {} cycles while spending {}", ticked, spent);
+        self.tick(spent.saturating_sub(ticked));
+    }
 
-            if requested && enabled {
-                // Clear the interrupt
-                self.mem.borrow_mut().clear_register_flag(MREG_IF, interrupt.flag);
+    // Executes the next instruction, or idles for one machine cycle while
+    // halted or stopped. Returns the executed instruction, if any.
+    pub fn step(&mut self) -> Result<Option<Instruction>> {
+        let start = self.cycles;
+        self.ticked.set(0);
+
+        let instruction = match self.state {
+            CPUState::Running => Some(self.execute_next()?),
+            CPUState::Halted | CPUState::Stopped => {
+                if self.state == CPUState::Stopped &&
+                    self.mem.borrow().load(MREG_P1) & 0x0f != 0x0f {
+                    self.set_state(CPUState::Running);
+                }
+                self.cycles += IDLE_CYCLES;
+                self.execute_interrupts();
+                None
+            },
+        };
 
-                // Disable further interrupts
-                self.disable_interrupts();
+        self.settle_cycles(start, 0);
+        Ok(instruction)
+    }
 
-                // Execute interrupt
-                self.pc = interrupt.handler_addr as u16;
-            }
+    fn pending_interrupts(&self) -> u8 {
+        let mem = self.mem.borrow();
+        mem.load_unchecked(MREG_IF) & mem.load_unchecked(MREG_IE) & 0x1f
+    }
+
+    // Services the highest priority interrupt that is both requested and
+    // enabled. Any such interrupt also ends HALT, even when IME is off.
+    pub fn execute_interrupts(&mut self) {
+        let pending = self.pending_interrupts();
+        if pending == 0 {
+            return
+        }
+        if self.state == CPUState::Halted {
+            self.set_state(CPUState::Running);
+        }
+        if !self.ime {
+            return
         }
+
+        let interrupt = match INTERRUPTS.iter().find(|i| pending & i.flag != 0) {
+            Some(interrupt) => interrupt,
+            None => return,
+        };
+        self.mem.borrow_mut().clear_register_flag(MREG_IF, interrupt.flag);
+        self.disable_interrupts();
+
+        self.internal_cycle();
+        self.internal_cycle();
+        let addr = self.pc;
+        self.stack_push_u16(addr);
+        self.pc = interrupt.handler_addr as u16;
+        self.cycles += INTERRUPT_DISPATCH_CYCLES;
     }
 
     pub fn reset(&mut self) {
@@ -267,7 +479,9 @@ impl CPU {
         self.flag = 0;
         self.cycles = 0;
         self.state = CPUState::Running;
-        self.enable_interrupts();
+        self.halt_bug = false;
+        self.breakpoint = false;
+        self.disable_interrupts();
         self.mem.borrow_mut().clear();
     }
 }
@@ -278,6 +492,8 @@ mod tests {
     use definition::Operand;
     use memory::Memory;
     use std::fs::File;
+    use joypad::Button;
+    use test_helpers::{execute_instruction, test_cpu};
 
     #[test]
     fn test_parse() {
@@ -333,6 +549,288 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_init_post_boot() {
+        let mut cpu = test_cpu();
+        cpu.init_post_boot();
+        assert_eq!(cpu.read_reg_short(REG_A, REG_F), 0x01b0);
+        assert_eq!(cpu.read_reg_short(REG_H, REG_L), 0x014d);
+        assert!(cpu.flag_is_set(FLAG_Z));
+        assert!(cpu.flag_is_set(FLAG_H));
+        assert!(cpu.flag_is_set(FLAG_C));
+        assert_eq!(cpu.sp, 0xfffe);
+        assert_eq!(cpu.pc, 0x0100);
+    }
+
+    #[test]
+    fn test_interrupt_dispatch() {
+        let mut cpu = test_cpu();
+        cpu.pc = 0x1234;
+        cpu.sp = 0xfffe;
+        cpu.enable_interrupts();
+        cpu.store_mem(MREG_IE, 0x1f);
+        cpu.store_mem(MREG_IF, INTERRUPT_TIMER.flag | INTERRUPT_JOYPAD.flag);
+
+        cpu.execute_interrupts();
+        assert_eq!(cpu.pc, INTERRUPT_TIMER.handler_addr as u16);
+        assert_eq!(cpu.sp, 0xfffc);
+        // The return address is stored little endian
+        assert_eq!(cpu.load_mem(0xfffc), 0x34);
+        assert_eq!(cpu.load_mem(0xfffd), 0x12);
+        assert_eq!(cpu.cycles, 20);
+        assert!(!cpu.ime);
+        // Only the highest priority interrupt is serviced
+        assert_eq!(cpu.load_mem(MREG_IF) & 0x1f, INTERRUPT_JOYPAD.flag);
+
+        // RETI returns to the interrupted code
+        execute_instruction(&mut cpu, 0xd9, None);
+        assert_eq!(cpu.pc, 0x1234);
+        assert!(cpu.ime);
+    }
+
+    #[test]
+    fn test_interrupts_disabled() {
+        let mut cpu = test_cpu();
+        cpu.pc = 0x1234;
+        cpu.store_mem(MREG_IE, 0x1f);
+        cpu.mem.borrow_mut().set_interrupt_flag(INTERRUPT_VBLANK.flag);
+        cpu.execute_interrupts();
+        assert_eq!(cpu.pc, 0x1234);
+        assert_eq!(cpu.load_mem(MREG_IF) & 0x1f, INTERRUPT_VBLANK.flag);
+    }
+
+    #[test]
+    fn test_ei_delay() {
+        let mut cpu = test_cpu();
+        cpu.store_mem(MREG_IE, 0x1f);
+        cpu.store_mem(MREG_IF, INTERRUPT_VBLANK.flag);
+        cpu.store_mem(0x100, 0xfb); // EI
+        cpu.store_mem(0x101, 0x00); // NOP
+        cpu.pc = 0x100;
+        cpu.sp = 0xfffe;
+
+        cpu.execute_next().unwrap();
+        assert_eq!(cpu.pc, 0x101);
+        cpu.execute_next().unwrap();
+        assert_eq!(cpu.pc, INTERRUPT_VBLANK.handler_addr as u16);
+    }
+
+    #[test]
+    fn test_halt_wakeup() {
+        let mut cpu = test_cpu();
+        cpu.set_state(CPUState::Halted);
+        cpu.execute_interrupts();
+        assert_eq!(cpu.state, CPUState::Halted);
+
+        // Wakes up without IME, continuing after the HALT
+        cpu.pc = 0x1234;
+        cpu.store_mem(MREG_IE, INTERRUPT_TIMER.flag);
+        cpu.store_mem(MREG_IF, INTERRUPT_TIMER.flag);
+        cpu.execute_interrupts();
+        assert_eq!(cpu.state, CPUState::Running);
+        assert_eq!(cpu.pc, 0x1234);
+    }
+
+    #[test]
+    fn test_halted_step() {
+        let mut cpu = test_cpu();
+        cpu.pc = 0x100;
+        cpu.store_mem(0x100, 0x76); // HALT
+        cpu.store_mem(0x101, 0x3c); // INC A
+        cpu.step().unwrap();
+        assert_eq!(cpu.state, CPUState::Halted);
+
+        let cycles = cpu.cycles;
+        assert!(cpu.step().unwrap().is_none());
+        assert_eq!(cpu.cycles, cycles + 4);
+        assert_eq!(cpu.pc, 0x101);
+
+        cpu.store_mem(MREG_IE, INTERRUPT_TIMER.flag);
+        cpu.mem.borrow_mut().set_interrupt_flag(INTERRUPT_TIMER.flag);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.reg[REG_A], 1);
+    }
+
+    #[test]
+    fn test_halt_bug() {
+        let mut cpu = test_cpu();
+        cpu.pc = 0x100;
+        cpu.store_mem(0x100, 0x76); // HALT
+        cpu.store_mem(0x101, 0x3e); // LD A, $14
+        cpu.store_mem(0x102, 0x14);
+        cpu.store_mem(MREG_IE, INTERRUPT_TIMER.flag);
+        cpu.store_mem(MREG_IF, INTERRUPT_TIMER.flag);
+
+        cpu.step().unwrap();
+        assert_eq!(cpu.state, CPUState::Running);
+
+        // The opcode is read again as the immediate
+        cpu.step().unwrap();
+        assert_eq!(cpu.reg[REG_A], 0x3e);
+        assert_eq!(cpu.pc, 0x102);
+    }
+
+    #[test]
+    fn test_halt_after_ei() {
+        let mut cpu = test_cpu();
+        cpu.pc = 0x100;
+        cpu.sp = 0xfffe;
+        cpu.store_mem(0x100, 0xfb); // EI
+        cpu.store_mem(0x101, 0x76); // HALT
+        cpu.store_mem(0x102, 0x3c); // INC A
+        cpu.store_mem(INTERRUPT_TIMER.handler_addr, 0xd9); // RETI
+        cpu.store_mem(MREG_IE, INTERRUPT_TIMER.flag);
+        cpu.store_mem(MREG_IF, INTERRUPT_TIMER.flag);
+
+        // The interrupt is taken with the HALT as return address
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.pc, INTERRUPT_TIMER.handler_addr as u16);
+        assert_eq!(cpu.load_mem(0xfffc), 0x01);
+        assert_eq!(cpu.load_mem(0xfffd), 0x01);
+
+        // After returning, the HALT halts as usual
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.state, CPUState::Halted);
+        assert_eq!(cpu.pc, 0x102);
+        assert_eq!(cpu.reg[REG_A], 0);
+    }
+
+    #[test]
+    fn test_stop_until_button() {
+        let mut cpu = test_cpu();
+        cpu.store_mem(MREG_P1, 0x10);
+        execute_instruction(&mut cpu, 0x10, None);
+        assert!(cpu.step().unwrap().is_none());
+        assert_eq!(cpu.state, CPUState::Stopped);
+
+        cpu.mem.borrow_mut().joypad_mut().set_button(Button::A, true);
+        cpu.step().unwrap();
+        assert_eq!(cpu.state, CPUState::Running);
+    }
+
+    #[test]
+    fn test_access_timing() {
+        let mut cpu = test_cpu();
+        let mem = Rc::clone(&cpu.mem);
+        let total = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&total);
+        // Keeps the number of cycles passed so far in HRAM
+        cpu.set_tick_hook(Some(Box::new(move |cycles| {
+            counter.set(counter.get() + cycles);
+            mem.borrow_mut().store_unchecked(HRAM_START, counter.get() as u8);
+        })));
+
+        cpu.pc = 0x100;
+        cpu.store_reg_short(REG_H, REG_L, HRAM_START as u16);
+        cpu.mem.borrow_mut().store(0x100, 0x7e); // LD A, (HL)
+        cpu.mem.borrow_mut().store(0x101, 0xcd); // CALL $1234
+        cpu.mem.borrow_mut().store(0x102, 0x34);
+        cpu.mem.borrow_mut().store(0x103, 0x12);
+        cpu.sp = 0xdffe;
+
+        // The read happens after the opcode fetch and its own cycle
+        cpu.step().unwrap();
+        assert_eq!(cpu.reg[REG_A], 8);
+
+        cpu.step().unwrap();
+        assert_eq!(total.get(), 8 + 24);
+
+        // Without a hook time only passes through cpu.cycles
+        cpu.set_tick_hook(None);
+        cpu.step().unwrap();
+        assert_eq!(total.get(), 32);
+    }
+
+    #[test]
+    fn test_ticks_match_cycles() {
+        for code in (0..512).filter(|&c| c != 0xcb) {
+            for &taken in &[false, true] {
+                let mut cpu = test_cpu();
+                let total = Rc::new(Cell::new(0));
+                let counter = Rc::clone(&total);
+                cpu.set_tick_hook(Some(Box::new(move |cycles| counter.set(counter.get() + cycles))));
+
+                cpu.pc = 0xc000;
+                cpu.sp = 0xdffe;
+                cpu.store_reg_short(REG_H, REG_L, 0xc100);
+                cpu.flag = if taken { FLAG_Z | FLAG_C } else { 0 };
+                let mut mem = cpu.mem.borrow_mut();
+                if code > 0xff {
+                    mem.store(0xc000, 0xcb);
+                    mem.store(0xc001, code as u8);
+                } else {
+                    mem.store(0xc000, code as u8);
+                }
+                drop(mem);
+
+                cpu.step().unwrap();
+                assert!(cpu.cycles > 0, "{:02x}", code);
+                assert_eq!(total.get(), cpu.cycles, "{:02x}", code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_call_push_timing() {
+        let mut cpu = test_cpu();
+        let mem = Rc::clone(&cpu.mem);
+        let total = Rc::new(Cell::new(0));
+        let pushed_at = Rc::new(Cell::new(None));
+        let (counter, pushed) = (Rc::clone(&total), Rc::clone(&pushed_at));
+        // Remembers the time when the high byte of the return address
+        // showed up on the stack
+        cpu.set_tick_hook(Some(Box::new(move |cycles| {
+            if pushed.get().is_none() && mem.borrow().load(0xdffd) != 0 {
+                pushed.set(Some(counter.get()));
+            }
+            counter.set(counter.get() + cycles);
+        })));
+
+        cpu.pc = 0x0100;
+        cpu.sp = 0xdffe;
+        cpu.mem.borrow_mut().store(0x100, 0xcd); // CALL $1234
+        cpu.mem.borrow_mut().store(0x101, 0x34);
+        cpu.mem.borrow_mut().store(0x102, 0x12);
+        cpu.step().unwrap();
+
+        // Three fetches and an internal cycle precede the push
+        assert_eq!(pushed_at.get(), Some(20));
+    }
+
+    #[test]
+    fn test_halt_bug_timing() {
+        let mut cpu = test_cpu();
+        let total = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&total);
+        cpu.set_tick_hook(Some(Box::new(move |cycles| counter.set(counter.get() + cycles))));
+
+        cpu.pc = 0xc000;
+        cpu.mem.borrow_mut().store(0xc000, 0x76); // HALT
+        cpu.mem.borrow_mut().store(0xc001, 0x3e); // LD A, n
+        cpu.mem.borrow_mut().store(MREG_IE, 0x01);
+        cpu.mem.borrow_mut().store(MREG_IF, 0x01);
+        cpu.step().unwrap();
+
+        // The operand is read from where the opcode was
+        cpu.step().unwrap();
+        assert_eq!(cpu.reg[REG_A], 0x3e);
+        assert_eq!(cpu.pc, 0xc002);
+        assert_eq!(total.get(), cpu.cycles);
+    }
+
+    #[test]
+    fn test_breakpoint() {
+        let mut cpu = test_cpu();
+        execute_instruction(&mut cpu, 0x41, None);
+        assert!(!cpu.take_breakpoint());
+        execute_instruction(&mut cpu, 0x40, None);
+        assert!(cpu.take_breakpoint());
+        assert!(!cpu.take_breakpoint());
+    }
+
     #[test]
     fn test_stack() {
         let mem = Rc::new(RefCell::new(Memory::default()));
@@ -11,9 +11,15 @@ impl Command for Continue {
 
     fn execute(self, debugger: &mut Debugger) {
         while !debugger.should_break() {
-            print!("${:04x}: ", debugger.cpu.pc);
-            let instruction = debugger.cpu.execute_next();
-            print!("{}\n", instruction);
+            let pc = debugger.cpu.pc;
+            match debugger.cpu.step() {
+                Ok(Some(instruction)) => println!("${:04x}: {}", pc, instruction),
+                Ok(None) => (),
+                Err(e) => {
+                    println!("{}", e);
+                    break
+                },
+            }
         };
         let instruction = debugger.cpu.current_instruction().unwrap();
         println!("${:04x}: {}", debugger.cpu.pc, instruction);
@@ -12,6 +12,6 @@ impl Command for List {
     fn execute(self, debugger: &mut Debugger) {
         print!("${:04x}: ", debugger.cpu.pc);
         let instruction = debugger.cpu.current_instruction().unwrap();
-        print!("{}\n", instruction);
+        println!("{}", instruction);
     }
 }
@@ -24,8 +24,8 @@ pub trait Command {
     fn execute(self, debugger: &mut Debugger);
 
     fn parse_number(s: &str) -> Option<u64> {
-        if s.starts_with("0x") {
-            u64::from_str_radix(&s[2..], 16).ok()
+        if let Some(hex) = s.strip_prefix("0x") {
+            u64::from_str_radix(hex, 16).ok()
         } else {
             s.parse::<u64>().ok()
         }
@@ -10,7 +10,9 @@ impl Command for Next {
     }
 
     fn execute(self, debugger: &mut Debugger) {
-        debugger.cpu.execute_next();
+        if let Err(e) = debugger.cpu.step() {
+            println!("{}", e);
+        }
         let instruction = debugger.cpu.current_instruction().unwrap();
         println!("${:04x}: {}", debugger.cpu.pc, instruction);
     }
@@ -20,8 +20,12 @@ impl<'a> Debugger<'a> {
         let bytes_read = mem.borrow_mut().load_rom(rom).unwrap();
         println!("Loaded {} byte rom", bytes_read);
 
+        mem.borrow_mut().init_post_boot();
+        let mut cpu = CPU::new(Rc::clone(&mem));
+        cpu.init_post_boot();
+
         Debugger {
-            cpu: CPU::new(Rc::clone(&mem)),
+            cpu,
             rom,
             breakpoints: vec![],
             prev_cmd: String::from("n")
@@ -38,7 +42,7 @@ impl<'a> Debugger<'a> {
 
     pub fn should_break(&self) -> bool {
         self.breakpoints.iter()
-            .any(|ref b| b.0 == self.cpu.pc)
+            .any(|b| b.0 == self.cpu.pc)
     }
 
     fn read_input(&self) -> String {
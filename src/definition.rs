@@ -152,12 +152,13 @@ pub struct Definition {
     pub immediate_size: Option<ImmediateType>,
 }
 
+// Executed like a NOP, taking the time of its opcode fetch
 pub const INVALID: Definition = Definition {
     mnemonic: Mnemonic::INVALID,
     code: 0xffff,
     length: 1,
     operands: [Operand::None, Operand::None],
-    cycles: [0, 0],
+    cycles: [4, 4],
     flags: [
         Flag::Unchanged,
         Flag::Unchanged,
@@ -0,0 +1,100 @@
+use constants::*;
+use std::ops::Range;
+
+// Bytes copied by a transfer, filling all of OAM
+const DMA_BYTES: usize = OAM_END - OAM_START + 1;
+// One byte is copied per machine cycle
+const DMA_CYCLES_PER_BYTE: usize = 4;
+
+#[derive(Debug)]
+pub struct Dma {
+    source: usize,
+    copied: usize,
+    cycles: usize,
+    active: bool,
+}
+
+impl Dma {
+    pub fn new() -> Dma {
+        Dma {
+            source: 0,
+            copied: 0,
+            cycles: 0,
+            active: false,
+        }
+    }
+
+    // Starting a new transfer restarts it from the first byte, even if
+    // one is already running
+    pub fn start(&mut self, value: u8) {
+        self.source = (value as usize) << 8;
+        self.copied = 0;
+        self.cycles = 0;
+        self.active = true;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    // Source address of the n:th byte of the transfer. Sources above
+    // 0xdfff read from the echo of work RAM.
+    pub fn source_addr(&self, n: usize) -> usize {
+        let addr = self.source + n;
+        if addr >= ECHO_START {
+            addr - (ECHO_START - WRAM_START)
+        } else {
+            addr
+        }
+    }
+
+    // Advances the transfer, returning the offsets of the bytes that are
+    // due to be copied
+    pub fn tick(&mut self, cycles: usize) -> Range<usize> {
+        if !self.active {
+            return 0..0
+        }
+
+        self.cycles += cycles;
+        let start = self.copied;
+        let end = (self.cycles / DMA_CYCLES_PER_BYTE).min(DMA_BYTES);
+        self.copied = end;
+        if end == DMA_BYTES {
+            self.active = false;
+        }
+        start..end
+    }
+}
+
+impl Default for Dma {
+    fn default() -> Dma {
+        Dma::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_duration() {
+        let mut dma = Dma::new();
+        dma.start(0xc1);
+        assert!(dma.is_active());
+        assert_eq!(dma.tick(6), 0..1);
+        assert_eq!(dma.tick(2), 1..2);
+        assert_eq!(dma.tick(4 * 200), 2..DMA_BYTES);
+        assert!(!dma.is_active());
+        assert_eq!(dma.tick(4), 0..0);
+    }
+
+    #[test]
+    fn test_source_addr() {
+        let mut dma = Dma::new();
+        dma.start(0xc1);
+        assert_eq!(dma.source_addr(0x10), 0xc110);
+
+        dma.start(0xfe);
+        assert_eq!(dma.source_addr(0x10), 0xde10);
+    }
+}
@@ -1,70 +1,349 @@
 use constants::*;
-use cpu::CPU;
+use cpu::{CPU, TickHook};
 use timer::Timer;
+use lcd::LCD;
+use audio::{self, AudioSink, Resampler, WavWriter};
+use errors::*;
+use frontend::{Frontend, NullFrontend};
 use std::fs::File;
+use std::io::BufWriter;
 use memory::Memory;
+use serial::LinkEndpoint;
+use cartridge::RtcClock;
 use std::time::SystemTime;
 use std::time::Duration;
 use std::thread;
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+// Advances everything but the CPU
+fn tick_hardware(mem: &Rc<RefCell<Memory>>, timer: &Rc<RefCell<Timer>>,
+                 lcd: &Rc<RefCell<LCD>>, cycles: usize) {
+    timer.borrow_mut().increase(cycles);
+    mem.borrow_mut().update(cycles);
+    lcd.borrow_mut().update(cycles);
+}
+
+// Fill level of the audio buffer that emulation is paced against
+const AUDIO_TARGET_FILL: f32 = 0.5;
 
 pub struct Emulator<'a> {
+    mem: Rc<RefCell<Memory>>,
     cpu: CPU,
-    timer: Timer,
+    timer: Rc<RefCell<Timer>>,
+    lcd: Rc<RefCell<LCD>>,
+    fast: bool,
+    throttle: bool,
+    stop_at_breakpoint: bool,
+    at_breakpoint: bool,
+    frontend: Box<dyn Frontend>,
+    audio: Option<Box<dyn AudioSink>>,
+    resampler: Resampler,
+    recording: Option<WavWriter<BufWriter<File>>>,
+    save_path: Option<PathBuf>,
+    frame_count: usize,
+    screenshot: Option<(usize, PathBuf)>,
     pub rom: &'a File,
 }
 
 impl<'a> Emulator<'a> {
-    pub fn new(rom: &'a mut File) -> Self {
+    pub fn new(rom: &'a mut File) -> Result<Self> {
+        let mut emu = Emulator::create(rom)?;
+        emu.cpu.init_post_boot();
+        emu.mem.borrow_mut().init_post_boot();
+        Ok(emu)
+    }
+
+    // Starts execution in the boot rom, with the cartridge mapped underneath
+    pub fn with_boot_rom(rom: &'a mut File, boot_rom: &mut File) -> Result<Self> {
+        let emu = Emulator::create(rom)?;
+        emu.mem.borrow_mut().load_boot_rom(boot_rom)?;
+        Ok(emu)
+    }
+
+    fn create(rom: &'a mut File) -> Result<Self> {
         let mem = Rc::new(RefCell::new(Memory::default()));
 
-        let bytes_read = mem.borrow_mut().load_rom(rom).unwrap();
-        println!("Loaded {} byte rom", bytes_read);
+        mem.borrow_mut().load_rom(rom)?;
 
-        Emulator {
+        let mut emu = Emulator {
+            mem: Rc::clone(&mem),
             cpu: CPU::new(Rc::clone(&mem)),
-            timer: Timer::new(Rc::clone(&mem)),
+            timer: Rc::new(RefCell::new(Timer::new(Rc::clone(&mem)))),
+            lcd: Rc::new(RefCell::new(LCD::new(Rc::clone(&mem)))),
+            fast: false,
+            throttle: true,
+            stop_at_breakpoint: false,
+            at_breakpoint: false,
+            frontend: Box::new(NullFrontend),
+            audio: None,
+            resampler: Resampler::new(),
+            recording: None,
+            save_path: None,
+            frame_count: 0,
+            screenshot: None,
             rom,
+        };
+        emu.set_fast_mode(false);
+        Ok(emu)
+    }
+
+    fn tick_hook(&self) -> TickHook {
+        let mem = Rc::clone(&self.mem);
+        let timer = Rc::clone(&self.timer);
+        let lcd = Rc::clone(&self.lcd);
+        Box::new(move |cycles| tick_hardware(&mem, &timer, &lcd, cycles))
+    }
+
+    // By default the timer, LCD and DMA are advanced on every memory
+    // access of the CPU. Fast mode only advances them between
+    // instructions, which is less accurate but cheaper.
+    pub fn set_fast_mode(&mut self, fast: bool) {
+        self.fast = fast;
+        let hook = if fast { None } else { Some(self.tick_hook()) };
+        self.cpu.set_tick_hook(hook);
+    }
+
+    // Without throttling, frames are run as fast as possible, for tests
+    // and other headless use
+    pub fn set_throttle(&mut self, throttle: bool) {
+        self.throttle = throttle;
+    }
+
+    // Stops running as soon as the CPU executes LD B,B
+    pub fn set_stop_at_breakpoint(&mut self, stop: bool) {
+        self.stop_at_breakpoint = stop;
+    }
+
+    pub fn at_breakpoint(&self) -> bool {
+        self.at_breakpoint
+    }
+
+    pub fn cpu(&self) -> &CPU {
+        &self.cpu
+    }
+
+    // Frames are discarded unless a frontend is set
+    pub fn set_frontend(&mut self, frontend: Box<dyn Frontend>) {
+        self.frontend = frontend;
+    }
+
+    // With an audio sink, emulation speed follows audio playback instead
+    // of the system clock
+    pub fn set_audio_sink(&mut self, sink: Box<dyn AudioSink>) {
+        self.mem.borrow_mut().apu_mut().set_sample_rate(sink.sample_rate());
+        self.audio = Some(sink);
+    }
+
+    // Records the sound output at the APU sample rate until
+    // `stop_recording` is called or the emulator is dropped
+    pub fn record_audio(&mut self, path: &Path) -> Result<()> {
+        self.stop_recording()?;
+        let sample_rate = self.mem.borrow().apu().sample_rate();
+        self.recording = Some(WavWriter::create(path, sample_rate)?);
+        Ok(())
+    }
+
+    pub fn stop_recording(&mut self) -> Result<()> {
+        match self.recording.take() {
+            Some(wav) => wav.finish().map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    // Saves a screenshot once `frame` frames have been drawn, and stops
+    pub fn set_screenshot_at_frame(&mut self, frame: usize, path: PathBuf) {
+        self.screenshot = Some((frame, path));
+    }
+
+    // Plugs something into the link port, which is left unconnected
+    // otherwise
+    pub fn set_link(&mut self, link: Box<dyn LinkEndpoint>) {
+        self.mem.borrow_mut().serial_mut().set_link(link);
+    }
+
+    pub fn set_rtc_clock(&mut self, clock: RtcClock) {
+        let mut mem = self.mem.borrow_mut();
+        if let Some(rtc) = mem.cartridge.as_mut().and_then(|c| c.rtc_mut()) {
+            rtc.set_clock(clock);
+        }
+    }
+
+    // Loads battery backed RAM from `path`, and keeps writing it back there
+    pub fn set_save_path(&mut self, path: PathBuf) {
+        if let Some(ref mut cart) = self.mem.borrow_mut().cartridge {
+            if let Err(e) = cart.load_save_file(&path) {
+                println!("Failed to load save: {}", e);
+            }
         }
+        self.save_path = Some(path);
     }
 
-    fn update(&mut self) {
+    pub fn flush_save(&mut self) {
+        self.write_save(false);
+    }
+
+    // The clock of a cart keeps running without RAM being written, so it
+    // is saved on exit regardless
+    fn write_save(&mut self, exiting: bool) {
+        let path = match self.save_path {
+            Some(ref path) => path,
+            None => return,
+        };
+        if let Some(ref mut cart) = self.mem.borrow_mut().cartridge {
+            if cart.is_ram_dirty() || (exiting && cart.has_rtc()) {
+                if let Err(e) = cart.write_save_file(path) {
+                    println!("Failed to write save: {}", e);
+                }
+            }
+        }
+    }
+
+    // Runs one frame, returns false when the frontend asked to quit
+    fn update(&mut self) -> Result<bool> {
         let cycles_per_frame = CLOCK_SPEED / FRAME_RATE;
         let mut cycle_count = 0;
 
         while cycle_count < cycles_per_frame {
-            let instruction = self.cpu.execute_next();
-            let cycles = instruction.definition.cycles[0];
-            self.timer.increase(cycles);
+            // Includes time spent halted and dispatching interrupts
+            let start = self.cpu.cycles;
+            self.cpu.step()?;
+            let cycles = self.cpu.cycles - start;
+            if self.fast {
+                tick_hardware(&self.mem, &self.timer, &self.lcd, cycles);
+            }
             cycle_count += cycles;
+            if self.cpu.take_breakpoint() && self.stop_at_breakpoint {
+                self.at_breakpoint = true;
+                return Ok(false)
+            }
         }
-        // self.lcd.update_frame();
+        self.frontend.present(self.lcd.borrow().frame());
+        self.frame_count += 1;
+        self.queue_audio();
+
+        if let Some((frame, ref path)) = self.screenshot {
+            if self.frame_count >= frame {
+                match self.lcd.borrow().save_screenshot(path) {
+                    Ok(()) => println!("Saved screenshot to {}", path.display()),
+                    Err(e) => println!("Failed to save screenshot: {}", e),
+                }
+                return Ok(false)
+            }
+        }
+        let mut mem = self.mem.borrow_mut();
+        Ok(self.frontend.poll_events(mem.joypad_mut()))
     }
 
-    pub fn run(&mut self) {
-        for _ in 0..10 {
-            // Run for 10 seconds
-            for _ in 0..60 {
-                let start = SystemTime::now();
-                self.update();
+    // Passes the samples of the last frame on to the recording and the
+    // audio sink. Those played back are stretched or squeezed slightly to
+    // keep the sink's buffer half full.
+    fn queue_audio(&mut self) {
+        let samples: Vec<f32> = self.mem.borrow_mut().apu_mut().drain_samples().collect();
+
+        if let Some(ref mut wav) = self.recording {
+            if let Err(e) = wav.write_samples(&samples) {
+                println!("Failed to record audio: {}", e);
+                self.recording = None;
+            }
+        }
+
+        if let Some(ref mut audio) = self.audio {
+            let ratio = audio::dynamic_ratio(audio.fill_level());
+            let mut out = Vec::with_capacity(samples.len() + 16);
+            self.resampler.process(&samples, ratio, &mut out);
+            audio.queue(&out);
+        }
+    }
+
+    // Runs one frame at normal speed, returns false when it is time to stop
+    fn run_frame(&mut self) -> Result<bool> {
+        let start = SystemTime::now();
+        let running = self.update()?;
+        let frame_time = Duration::new(0, 1_000_000_000u32 / FRAME_RATE as u32);
+
+        match self.audio {
+            _ if !self.throttle => (),
+            // Wait for the audio device to play back the buffered samples,
+            // but never stall for long if it stopped consuming them
+            Some(ref audio) => {
+                while audio.fill_level() > AUDIO_TARGET_FILL &&
+                    start.elapsed().unwrap() < frame_time * 2 {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            },
+            None => {
                 let dur = start.elapsed().unwrap();
-                let diff = Duration::new(0, 1_000_000_000u32 / 60).checked_sub(dur)
+                let diff = frame_time.checked_sub(dur)
                     .unwrap_or_else(|| Duration::new(0, 0));
                 thread::sleep(diff);
+            },
+        }
+
+        // Write battery backed RAM about once a second
+        if self.frame_count.is_multiple_of(FRAME_RATE) {
+            self.flush_save();
+        }
+        Ok(running)
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        while self.run_frame()? {}
+        Ok(())
+    }
+
+    pub fn run_for(&mut self, frames: usize) -> Result<()> {
+        for _ in 0..frames {
+            if !self.run_frame()? {
+                break
             }
         }
+        Ok(())
+    }
+}
+
+impl<'a> Drop for Emulator<'a> {
+    fn drop(&mut self) {
+        self.write_save(true);
+        if let Err(e) = self.stop_recording() {
+            println!("Failed to finish audio recording: {}", e);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::env;
+    use std::io::Write;
 
     #[test]
     fn test_start_rom() {
         let mut rom = File::open("/home/kalle/temp/boot.gb").unwrap();
-        let mut emu = Emulator::new(&mut rom);
-        emu.run();
+        let mut emu = Emulator::new(&mut rom).unwrap();
+        // Run for 10 seconds
+        emu.run_for(600).unwrap();
+    }
+
+    // Tests run in parallel, so each gets a file of its own
+    fn unsupported_cartridge(name: &str) -> File {
+        let path = env::temp_dir().join(format!("gameboy-{}.gb", name));
+        let mut rom = vec![0u8; 2 * ROM_BANK_SIZE];
+        rom[HEADER_CARTRIDGE_TYPE] = 0xfc;
+        File::create(&path).unwrap().write_all(&rom).unwrap();
+        File::open(&path).unwrap()
+    }
+
+    #[test]
+    fn test_unsupported_cartridge() {
+        assert!(Emulator::new(&mut unsupported_cartridge("unsupported")).is_err());
+    }
+
+    #[test]
+    fn test_boot_rom_with_unsupported_cartridge() {
+        let mut rom = unsupported_cartridge("unsupported-boot");
+        let mut boot_rom = unsupported_cartridge("unsupported-boot-rom");
+        assert!(Emulator::with_boot_rom(&mut rom, &mut boot_rom).is_err());
     }
 }
\ No newline at end of file
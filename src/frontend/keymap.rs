@@ -0,0 +1,117 @@
+use sdl2::controller::Button as ControllerButton;
+use sdl2::keyboard::Keycode;
+use errors::*;
+use joypad::Button;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+pub struct KeyMap {
+    keys: Vec<(Keycode, Button)>,
+    controller: Vec<(ControllerButton, Button)>,
+}
+
+impl KeyMap {
+    pub fn new() -> KeyMap {
+        KeyMap {
+            keys: Vec::new(),
+            controller: Vec::new(),
+        }
+    }
+
+    pub fn bind_key(&mut self, key: Keycode, button: Button) {
+        self.keys.retain(|&(k, _)| k != key);
+        self.keys.push((key, button));
+    }
+
+    pub fn bind_controller_button(&mut self, pad_button: ControllerButton, button: Button) {
+        self.controller.retain(|&(b, _)| b != pad_button);
+        self.controller.push((pad_button, button));
+    }
+
+    pub fn key(&self, key: Keycode) -> Option<Button> {
+        self.keys.iter().find(|&&(k, _)| k == key).map(|&(_, b)| b)
+    }
+
+    pub fn controller_button(&self, pad_button: ControllerButton) -> Option<Button> {
+        self.controller.iter().find(|&&(b, _)| b == pad_button).map(|&(_, b)| b)
+    }
+
+    // Reads lines of `<button> = <key name>`, e.g. `start = Return`, using
+    // SDL's key names. Buttons that are not mentioned keep their default
+    // keys, the ones that are lose them.
+    pub fn parse(config: &str) -> Result<KeyMap> {
+        let mut map = KeyMap::default();
+        let mut rebound = Vec::new();
+
+        for line in config.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            let mut parts = line.splitn(2, '=').map(|p| p.trim());
+            let (name, key_name) = match (parts.next(), parts.next()) {
+                (Some(name), Some(key_name)) => (name, key_name),
+                _ => bail!("Invalid key binding: {}", line),
+            };
+            let button = Button::from_name(name)
+                .chain_err(|| format!("Unknown button: {}", name))?;
+            let key = Keycode::from_name(key_name)
+                .chain_err(|| format!("Unknown key: {}", key_name))?;
+
+            if !rebound.contains(&button) {
+                map.keys.retain(|&(_, b)| b != button);
+                rebound.push(button);
+            }
+            map.bind_key(key, button);
+        }
+        Ok(map)
+    }
+
+    pub fn load(path: &Path) -> Result<KeyMap> {
+        let mut config = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut config))
+            .chain_err(|| format!("Failed to read {}", path.display()))?;
+        KeyMap::parse(&config)
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> KeyMap {
+        let mut map = KeyMap::new();
+        map.bind_key(Keycode::Right, Button::Right);
+        map.bind_key(Keycode::Left, Button::Left);
+        map.bind_key(Keycode::Up, Button::Up);
+        map.bind_key(Keycode::Down, Button::Down);
+        map.bind_key(Keycode::X, Button::A);
+        map.bind_key(Keycode::Z, Button::B);
+        map.bind_key(Keycode::Backspace, Button::Select);
+        map.bind_key(Keycode::Return, Button::Start);
+
+        map.bind_controller_button(ControllerButton::DPadRight, Button::Right);
+        map.bind_controller_button(ControllerButton::DPadLeft, Button::Left);
+        map.bind_controller_button(ControllerButton::DPadUp, Button::Up);
+        map.bind_controller_button(ControllerButton::DPadDown, Button::Down);
+        map.bind_controller_button(ControllerButton::A, Button::A);
+        map.bind_controller_button(ControllerButton::B, Button::B);
+        map.bind_controller_button(ControllerButton::Back, Button::Select);
+        map.bind_controller_button(ControllerButton::Start, Button::Start);
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebind_key() {
+        let mut map = KeyMap::default();
+        assert_eq!(map.key(Keycode::X), Some(Button::A));
+
+        map.bind_key(Keycode::X, Button::Start);
+        assert_eq!(map.key(Keycode::X), Some(Button::Start));
+        assert_eq!(map.key(Keycode::Z), Some(Button::B));
+        assert_eq!(map.controller_button(ControllerButton::Back), Some(Button::Select));
+    }
+}
@@ -0,0 +1,60 @@
+use constants::*;
+use frontend::Frontend;
+use lcd::{FrameBuffer, Shade};
+use std::rc::Rc;
+use std::cell::RefCell;
+
+// Keeps a copy of the last presented frame. The frame is shared, so a test
+// can hand the frontend to the emulator and still inspect the output.
+#[derive(Debug, Clone)]
+pub struct MemoryFrontend {
+    frame: Rc<RefCell<FrameBuffer>>,
+    frames: Rc<RefCell<usize>>,
+}
+
+impl MemoryFrontend {
+    pub fn new() -> MemoryFrontend {
+        MemoryFrontend {
+            frame: Rc::new(RefCell::new([[Shade::Shade0; LCD_PIXELS_X]; LCD_PIXELS_Y])),
+            frames: Rc::new(RefCell::new(0)),
+        }
+    }
+
+    pub fn frame(&self) -> FrameBuffer {
+        *self.frame.borrow()
+    }
+
+    pub fn frame_count(&self) -> usize {
+        *self.frames.borrow()
+    }
+}
+
+impl Default for MemoryFrontend {
+    fn default() -> MemoryFrontend {
+        MemoryFrontend::new()
+    }
+}
+
+impl Frontend for MemoryFrontend {
+    fn present(&mut self, frame: &FrameBuffer) {
+        *self.frame.borrow_mut() = *frame;
+        *self.frames.borrow_mut() += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_frame() {
+        let frontend = MemoryFrontend::new();
+        let mut presenter = frontend.clone();
+        let mut frame = [[Shade::Shade0; LCD_PIXELS_X]; LCD_PIXELS_Y];
+        frame[10][20] = Shade::Shade3;
+
+        presenter.present(&frame);
+        assert_eq!(frontend.frame_count(), 1);
+        assert_eq!(frontend.frame()[10][20], Shade::Shade3);
+    }
+}
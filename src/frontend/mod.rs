@@ -0,0 +1,32 @@
+#[cfg(feature = "sdl")]
+mod keymap;
+mod memory;
+mod null;
+#[cfg(feature = "sdl")]
+mod sdl;
+#[cfg(feature = "sdl")]
+mod sdl_audio;
+
+pub use self::memory::MemoryFrontend;
+pub use self::null::NullFrontend;
+#[cfg(feature = "sdl")]
+pub use self::keymap::KeyMap;
+#[cfg(feature = "sdl")]
+pub use self::sdl::SdlFrontend;
+#[cfg(feature = "sdl")]
+pub use self::sdl_audio::SdlAudio;
+
+use joypad::Joypad;
+use lcd::FrameBuffer;
+
+// Presents the frames produced by the LCD, and feeds user input back to
+// the emulator
+pub trait Frontend {
+    fn present(&mut self, frame: &FrameBuffer);
+
+    // Handles pending input, passing button changes on to the joypad.
+    // Returns false once the user asked to quit.
+    fn poll_events(&mut self, _joypad: &mut Joypad) -> bool {
+        true
+    }
+}
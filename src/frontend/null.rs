@@ -0,0 +1,10 @@
+use frontend::Frontend;
+use lcd::FrameBuffer;
+
+// Discards every frame, for running without a display
+#[derive(Debug, Default)]
+pub struct NullFrontend;
+
+impl Frontend for NullFrontend {
+    fn present(&mut self, _frame: &FrameBuffer) {}
+}
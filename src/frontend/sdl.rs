@@ -0,0 +1,148 @@
+use sdl2;
+use sdl2::{EventPump, GameControllerSubsystem, Sdl};
+use sdl2::controller::GameController;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::render::WindowCanvas;
+use constants::*;
+use errors::*;
+use frontend::{Frontend, KeyMap, SdlAudio};
+use joypad::Joypad;
+use lcd::{FrameBuffer, Shade};
+use screenshot;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct SdlFrontend {
+    sdl_context: Sdl,
+    canvas: WindowCanvas,
+    events: EventPump,
+    frame: FrameBuffer,
+    key_map: KeyMap,
+    controller_subsystem: GameControllerSubsystem,
+    // Controllers stay open for as long as they are referenced
+    controllers: Vec<GameController>,
+}
+
+impl SdlFrontend {
+    pub fn new() -> Result<SdlFrontend> {
+        let sdl_context = sdl2::init()?;
+        let video_subsystem = sdl_context.video()?;
+
+        let window = video_subsystem.window(LCD_TITLE,
+                                            LCD_PIXELS_X as u32,
+                                            LCD_PIXELS_Y as u32)
+            .position_centered()
+            .build()
+            .chain_err(|| "Failed to create window")?;
+
+        let canvas = window.into_canvas().build().chain_err(|| "Failed to create canvas")?;
+        let events = sdl_context.event_pump()?;
+        let controller_subsystem = sdl_context.game_controller()?;
+        Ok(SdlFrontend {
+            sdl_context,
+            canvas,
+            events,
+            frame: [[Shade::Shade0; LCD_PIXELS_X]; LCD_PIXELS_Y],
+            key_map: KeyMap::default(),
+            controller_subsystem,
+            controllers: Vec::new(),
+        })
+    }
+
+    pub fn set_key_map(&mut self, key_map: KeyMap) {
+        self.key_map = key_map;
+    }
+
+    // Only one SDL context can exist, so audio is opened through the
+    // frontend that owns it
+    pub fn open_audio(&self) -> Result<SdlAudio> {
+        let audio_subsystem = self.sdl_context.audio()?;
+        SdlAudio::new(&audio_subsystem)
+    }
+
+    // SDL reports already connected controllers as added at startup too
+    fn open_controller(&mut self, index: u32) {
+        if !self.controller_subsystem.is_game_controller(index) {
+            return
+        }
+        match self.controller_subsystem.open(index) {
+            Ok(controller) => {
+                println!("Using controller {}", controller.name());
+                self.controllers.push(controller);
+            },
+            Err(e) => println!("Failed to open controller {}: {}", index, e),
+        }
+    }
+
+    // Saves the last presented frame in the working directory
+    fn save_screenshot(&self) {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = PathBuf::from(format!("screenshot-{}.{}", timestamp,
+                                         screenshot::default_extension()));
+        match screenshot::save(&self.frame, &path) {
+            Ok(()) => println!("Saved screenshot to {}", path.display()),
+            Err(e) => println!("Failed to save screenshot: {}", e),
+        }
+    }
+}
+
+impl Frontend for SdlFrontend {
+    fn present(&mut self, frame: &FrameBuffer) {
+        self.canvas.clear();
+        for (y, row) in frame.iter().enumerate() {
+            for (x, shade) in row.iter().enumerate() {
+                let (r, g, b) = shade.rgb();
+                let _ = self.canvas.pixel(x as i16, y as i16, Color::RGB(r, g, b));
+            }
+        }
+        self.canvas.present();
+        self.frame = *frame;
+    }
+
+    fn poll_events(&mut self, joypad: &mut Joypad) -> bool {
+        let mut screenshot = false;
+        let mut added = Vec::new();
+        for event in self.events.poll_iter() {
+            match event {
+                Event::Quit { .. } |
+                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return false,
+                Event::KeyDown { keycode: Some(Keycode::F12), .. } => screenshot = true,
+                Event::KeyDown { keycode: Some(key), .. } => {
+                    if let Some(button) = self.key_map.key(key) {
+                        joypad.set_button(button, true);
+                    }
+                },
+                Event::KeyUp { keycode: Some(key), .. } => {
+                    if let Some(button) = self.key_map.key(key) {
+                        joypad.set_button(button, false);
+                    }
+                },
+                Event::ControllerButtonDown { button, .. } => {
+                    if let Some(button) = self.key_map.controller_button(button) {
+                        joypad.set_button(button, true);
+                    }
+                },
+                Event::ControllerButtonUp { button, .. } => {
+                    if let Some(button) = self.key_map.controller_button(button) {
+                        joypad.set_button(button, false);
+                    }
+                },
+                Event::ControllerDeviceAdded { which, .. } => added.push(which),
+                _ => (),
+            }
+        }
+
+        for index in added {
+            self.open_controller(index);
+        }
+        if screenshot {
+            self.save_screenshot();
+        }
+        true
+    }
+}
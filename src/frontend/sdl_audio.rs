@@ -0,0 +1,74 @@
+use sdl2::AudioSubsystem;
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+use apu::DEFAULT_SAMPLE_RATE;
+use audio::{AudioSink, RingBuffer};
+use errors::*;
+
+// Frames requested by each device callback
+const DEVICE_FRAMES: u16 = 1024;
+// Buffered audio, in fractions of a second. Half of it is the target
+// latency kept by the dynamic rate control.
+const BUFFER_DIVISOR: usize = 8;
+
+struct Playback {
+    buffer: RingBuffer,
+}
+
+impl AudioCallback for Playback {
+    type Channel = f32;
+
+    // Plays silence if the emulator falls behind
+    fn callback(&mut self, out: &mut [f32]) {
+        let count = self.buffer.pop(out);
+        for sample in &mut out[count..] {
+            *sample = 0.0;
+        }
+    }
+}
+
+pub struct SdlAudio {
+    buffer: RingBuffer,
+    sample_rate: usize,
+    // Playback stops when the device is dropped
+    _device: AudioDevice<Playback>,
+}
+
+impl SdlAudio {
+    pub fn new(audio_subsystem: &AudioSubsystem) -> Result<SdlAudio> {
+        let desired = AudioSpecDesired {
+            freq: Some(DEFAULT_SAMPLE_RATE as i32),
+            channels: Some(2),
+            samples: Some(DEVICE_FRAMES),
+        };
+
+        let mut buffer = None;
+        let device = audio_subsystem.open_playback(None, &desired, |spec| {
+            let capacity = spec.freq as usize * 2 / BUFFER_DIVISOR;
+            let ring = RingBuffer::new(capacity.max(spec.samples as usize * 4));
+            buffer = Some(ring.clone());
+            Playback { buffer: ring }
+        })?;
+
+        let sample_rate = device.spec().freq as usize;
+        device.resume();
+        Ok(SdlAudio {
+            buffer: buffer.unwrap(),
+            sample_rate,
+            _device: device,
+        })
+    }
+}
+
+impl AudioSink for SdlAudio {
+    fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    fn queue(&mut self, samples: &[f32]) {
+        self.buffer.push(samples);
+    }
+
+    fn fill_level(&self) -> f32 {
+        self.buffer.fill_level()
+    }
+}
@@ -2852,7 +2852,7 @@ static INSTRUCTIONS: [Definition; 512] = [
         mnemonic: Mnemonic::BIT,
         code: 0xcb46,
         length: 2,
-        cycles: [16, 0],
+        cycles: [12, 0],
         operands: [Operand::Bit(0), Operand::RegisterPairAddr(6, 7)],
         flags: [Flag::Function, Flag::Reset, Flag::Set, Flag::Unchanged],
         immediate_size: None,
@@ -2924,7 +2924,7 @@ static INSTRUCTIONS: [Definition; 512] = [
         mnemonic: Mnemonic::BIT,
         code: 0xcb4e,
         length: 2,
-        cycles: [16, 0],
+        cycles: [12, 0],
         operands: [Operand::Bit(1), Operand::RegisterPairAddr(6, 7)],
         flags: [Flag::Function, Flag::Reset, Flag::Set, Flag::Unchanged],
         immediate_size: None,
@@ -2996,7 +2996,7 @@ static INSTRUCTIONS: [Definition; 512] = [
         mnemonic: Mnemonic::BIT,
         code: 0xcb56,
         length: 2,
-        cycles: [16, 0],
+        cycles: [12, 0],
         operands: [Operand::Bit(2), Operand::RegisterPairAddr(6, 7)],
         flags: [Flag::Function, Flag::Reset, Flag::Set, Flag::Unchanged],
         immediate_size: None,
@@ -3068,7 +3068,7 @@ static INSTRUCTIONS: [Definition; 512] = [
         mnemonic: Mnemonic::BIT,
         code: 0xcb5e,
         length: 2,
-        cycles: [16, 0],
+        cycles: [12, 0],
         operands: [Operand::Bit(3), Operand::RegisterPairAddr(6, 7)],
         flags: [Flag::Function, Flag::Reset, Flag::Set, Flag::Unchanged],
         immediate_size: None,
@@ -3140,7 +3140,7 @@ static INSTRUCTIONS: [Definition; 512] = [
         mnemonic: Mnemonic::BIT,
         code: 0xcb66,
         length: 2,
-        cycles: [16, 0],
+        cycles: [12, 0],
         operands: [Operand::Bit(4), Operand::RegisterPairAddr(6, 7)],
         flags: [Flag::Function, Flag::Reset, Flag::Set, Flag::Unchanged],
         immediate_size: None,
@@ -3212,7 +3212,7 @@ static INSTRUCTIONS: [Definition; 512] = [
         mnemonic: Mnemonic::BIT,
         code: 0xcb6e,
         length: 2,
-        cycles: [16, 0],
+        cycles: [12, 0],
         operands: [Operand::Bit(5), Operand::RegisterPairAddr(6, 7)],
         flags: [Flag::Function, Flag::Reset, Flag::Set, Flag::Unchanged],
         immediate_size: None,
@@ -3284,7 +3284,7 @@ static INSTRUCTIONS: [Definition; 512] = [
         mnemonic: Mnemonic::BIT,
         code: 0xcb76,
         length: 2,
-        cycles: [16, 0],
+        cycles: [12, 0],
         operands: [Operand::Bit(6), Operand::RegisterPairAddr(6, 7)],
         flags: [Flag::Function, Flag::Reset, Flag::Set, Flag::Unchanged],
         immediate_size: None,
@@ -3356,7 +3356,7 @@ static INSTRUCTIONS: [Definition; 512] = [
         mnemonic: Mnemonic::BIT,
         code: 0xcb7e,
         length: 2,
-        cycles: [16, 0],
+        cycles: [12, 0],
         operands: [Operand::Bit(7), Operand::RegisterPairAddr(6, 7)],
         flags: [Flag::Function, Flag::Reset, Flag::Set, Flag::Unchanged],
         immediate_size: None,
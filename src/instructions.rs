@@ -36,7 +36,7 @@ impl Instruction {
 
 impl fmt::Display for Instruction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let a = self.definition.operands.get(0).unwrap_or(&Operand::None);
+        let a = self.definition.operands.first().unwrap_or(&Operand::None);
         let b = self.definition.operands.get(1).unwrap_or(&Operand::None);
         let a_str = a.as_string(self.immediate);
         let b_str = b.as_string(self.immediate);
@@ -0,0 +1,158 @@
+// P1 bits selecting which half of the button matrix is read, active low
+const SELECT_DIRECTIONS: u8 = 0b0001_0000;
+const SELECT_BUTTONS: u8 = 0b0010_0000;
+const SELECT_MASK: u8 = SELECT_DIRECTIONS | SELECT_BUTTONS;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Button {
+    Right,
+    Left,
+    Up,
+    Down,
+    A,
+    B,
+    Select,
+    Start,
+}
+
+impl Button {
+    pub const ALL: [Button; 8] = [
+        Button::Right, Button::Left, Button::Up, Button::Down,
+        Button::A, Button::B, Button::Select, Button::Start,
+    ];
+
+    // Directions occupy the low nibble and buttons the high nibble of
+    // the pressed mask, in the order they appear in P1
+    fn mask(self) -> u8 {
+        1 << (self as u8)
+    }
+
+    pub fn from_name(name: &str) -> Option<Button> {
+        Button::ALL.iter()
+            .find(|b| format!("{:?}", b).eq_ignore_ascii_case(name))
+            .cloned()
+    }
+}
+
+#[derive(Debug)]
+pub struct Joypad {
+    pressed: u8,
+    select: u8,
+    interrupt: bool,
+}
+
+impl Joypad {
+    pub fn new() -> Joypad {
+        Joypad {
+            pressed: 0,
+            select: SELECT_MASK,
+            interrupt: false,
+        }
+    }
+
+    // The low nibble of P1, where a 0 bit is a pressed button on one of
+    // the selected lines
+    fn lines(&self) -> u8 {
+        let mut lines = 0;
+        if self.select & SELECT_DIRECTIONS == 0 {
+            lines |= self.pressed & 0x0f;
+        }
+        if self.select & SELECT_BUTTONS == 0 {
+            lines |= self.pressed >> 4;
+        }
+        !lines & 0x0f
+    }
+
+    // Any line going from high to low requests the joypad interrupt
+    fn update_lines<F: FnOnce(&mut Joypad)>(&mut self, f: F) {
+        let before = self.lines();
+        f(self);
+        if before & !self.lines() != 0 {
+            self.interrupt = true;
+        }
+    }
+
+    pub fn read(&self) -> u8 {
+        0b1100_0000 | self.select | self.lines()
+    }
+
+    pub fn write(&mut self, value: u8) {
+        self.update_lines(|j| j.select = value & SELECT_MASK);
+    }
+
+    pub fn set_button(&mut self, button: Button, pressed: bool) {
+        self.update_lines(|j| {
+            if pressed {
+                j.pressed |= button.mask();
+            } else {
+                j.pressed &= !button.mask();
+            }
+        });
+    }
+
+    pub fn is_pressed(&self, button: Button) -> bool {
+        self.pressed & button.mask() != 0
+    }
+
+    // Returns whether an interrupt was requested since the last call
+    pub fn take_interrupt(&mut self) -> bool {
+        let interrupt = self.interrupt;
+        self.interrupt = false;
+        interrupt
+    }
+}
+
+impl Default for Joypad {
+    fn default() -> Joypad {
+        Joypad::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_lines() {
+        let mut joypad = Joypad::new();
+        joypad.set_button(Button::Down, true);
+        joypad.set_button(Button::A, true);
+        assert_eq!(joypad.read(), 0xff);
+
+        joypad.write(SELECT_BUTTONS);
+        assert_eq!(joypad.read(), 0b1110_0111);
+
+        joypad.write(SELECT_DIRECTIONS);
+        assert_eq!(joypad.read(), 0b1101_1110);
+
+        joypad.write(0);
+        assert_eq!(joypad.read(), 0b1100_0110);
+    }
+
+    #[test]
+    fn test_interrupt_on_press() {
+        let mut joypad = Joypad::new();
+        joypad.write(SELECT_BUTTONS);
+        joypad.set_button(Button::Start, true);
+        assert!(!joypad.take_interrupt());
+
+        // Only presses on a selected line pull it low
+        joypad.set_button(Button::Left, true);
+        assert!(joypad.take_interrupt());
+        assert!(!joypad.take_interrupt());
+
+        joypad.set_button(Button::Left, false);
+        assert!(!joypad.take_interrupt());
+
+        // Selecting a line with a button held down also counts
+        joypad.write(0);
+        assert!(joypad.take_interrupt());
+    }
+
+    #[test]
+    fn test_button_from_name() {
+        assert_eq!(Button::from_name("start"), Some(Button::Start));
+        assert_eq!(Button::from_name("B"), Some(Button::B));
+        assert_eq!(Button::from_name("turbo"), None);
+    }
+}
@@ -1,12 +1,13 @@
-use sdl2;
-use sdl2::pixels::Color;
-use sdl2::gfx::primitives::DrawRenderer;
-use std::time::Duration;
 use std::rc::Rc;
 use std::cell::RefCell;
 use constants::*;
+use errors::*;
+use interrupts::*;
 use memory::Memory;
+use screenshot;
+use std::path::Path;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Shade {
     Shade0,
     Shade1,
@@ -14,117 +15,350 @@ pub enum Shade {
     Shade3,
 }
 
+impl Shade {
+    // Maps a 2 bit color number through a palette register such as BGP
+    pub fn from_palette(palette: u8, color: u8) -> Shade {
+        match (palette >> (color * 2)) & 0b11 {
+            0 => Shade::Shade0,
+            1 => Shade::Shade1,
+            2 => Shade::Shade2,
+            _ => Shade::Shade3,
+        }
+    }
+
+    // The green tinted colors of the original DMG screen
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Shade::Shade0 => (155, 188, 15),
+            Shade::Shade1 => (139, 172, 15),
+            Shade::Shade2 => (48, 98, 48),
+            Shade::Shade3 => (15, 56, 15),
+        }
+    }
+}
+
+pub type FrameBuffer = [[Shade; LCD_PIXELS_X]; LCD_PIXELS_Y];
+
+// An OAM entry, with the position still in screen coordinates offset by
+// (8, 16) as stored in memory
+#[derive(Debug, Clone, Copy)]
+struct Sprite {
+    y: u8,
+    x: u8,
+    tile: u8,
+    flags: u8,
+}
+
 pub struct LCD {
     mem: Rc<RefCell<Memory>>,
-    data: [[Color; LCD_PIXELS_X]; LCD_PIXELS_Y],  // 160x144 bits
-    canvas: sdl2::render::WindowCanvas,
+    data: FrameBuffer,
     state: u8,
     cycles: usize,
+    window_line: usize,
+    sprites: Vec<Sprite>,
+    sprite_height: usize,
+    stat_line: bool,
 }
 
 impl LCD {
     pub fn new(mem: Rc<RefCell<Memory>>) -> LCD {
-        let sdl_context = sdl2::init().unwrap();
-        let video_subsystem = sdl_context.video().unwrap();
-
-        let window = video_subsystem.window(LCD_TITLE,
-                                            LCD_PIXELS_X as u32,
-                                            LCD_PIXELS_Y as u32)
-            .position_centered()
-            .build()
-            .unwrap();
-
-        let canvas = window.into_canvas().build().unwrap();
         LCD {
             mem,
-            data: [[Color::RGB(155, 188, 15); LCD_PIXELS_X]; LCD_PIXELS_Y],
-            canvas,
-            state: LCD_MODE0_FLAG,
+            data: [[Shade::Shade0; LCD_PIXELS_X]; LCD_PIXELS_Y],
+            state: LCD_MODE2_FLAG,
             cycles: 0,
+            window_line: 0,
+            sprites: Vec::with_capacity(SPRITES_PER_LINE),
+            sprite_height: 8,
+            stat_line: false,
         }
-
     }
 
     pub fn set_pixel(&mut self, x: usize, y: usize, shade: Shade) {
-        self.data[y][x] = match shade {
-            Shade::Shade0 => Color::RGB(15, 56, 15),
-            Shade::Shade1 => Color::RGB(48, 98, 48),
-            Shade::Shade2 => Color::RGB(139, 172, 15),
-            Shade::Shade3 => Color::RGB(155, 188, 15),
-        }
+        self.data[y][x] = shade;
+    }
+
+    // The most recently drawn picture, for a frontend to present
+    pub fn frame(&self) -> &FrameBuffer {
+        &self.data
+    }
+
+    // Writes the current frame as PNG or PPM, depending on the extension
+    pub fn save_screenshot(&self, path: &Path) -> Result<()> {
+        screenshot::save(&self.data, path)
     }
 
     pub fn is_enabled(&self) -> bool {
         self.mem.borrow().is_set(MREG_LCDC, 7)
     }
 
-    pub fn show(&mut self) {
-        self.canvas.clear();
-        for y in 0..LCD_PIXELS_Y {
-            for x in 0..LCD_PIXELS_X {
-                let color = self.data[y][x];
-                self.canvas.pixel(x as i16, y as i16, color).unwrap();
-            }
+    // Address of the first byte of a tile, honouring the LCDC tile data select
+    fn tile_addr(lcdc: u8, tile: u8) -> usize {
+        if lcdc & LCDC_TILE_DATA != 0 {
+            TILE_DATA_UNSIGNED + (tile as usize) * TILE_BYTES
+        } else {
+            (TILE_DATA_SIGNED as isize + (tile as i8 as isize) * TILE_BYTES as isize) as usize
         }
-        self.canvas.present();
-        ::std::thread::sleep(Duration::new(10, 1_000_000_000u32 / 60));
     }
 
-    pub fn update_frame(&mut self) {
+    // Color number (0-3) of pixel (x, y) within a 256x256 tile map
+    fn tile_map_color(mem: &Memory, lcdc: u8, map: usize, x: usize, y: usize) -> u8 {
+        let tile = mem.load_unchecked(map + (y / 8) * TILE_MAP_WIDTH + x / 8);
+        let row = Self::tile_addr(lcdc, tile) + (y % 8) * 2;
+        let lo = mem.load_unchecked(row);
+        let hi = mem.load_unchecked(row + 1);
+        let bit = 7 - (x % 8);
+        (((hi >> bit) & 1) << 1) | ((lo >> bit) & 1)
+    }
+
+    fn render_background(mem: &Memory, lcdc: u8, ly: usize, line: &mut [u8]) {
+        let map = if lcdc & LCDC_BG_MAP != 0 { TILE_MAP_1 } else { TILE_MAP_0 };
+        let scx = mem.load_unchecked(MREG_SCX) as usize;
+        let scy = mem.load_unchecked(MREG_SCY) as usize;
+        let y = (ly + scy) & 0xff;
 
+        for (x, color) in line.iter_mut().enumerate() {
+            *color = Self::tile_map_color(mem, lcdc, map, (x + scx) & 0xff, y);
+        }
     }
 
-    fn increase_line_count(&self) -> u8 {
-        let mut mem = self.mem.borrow();
-        let ly = mem.load_unchecked(MREG_LY);
-        let new_ly = (ly + 1) % LY_MAX;
-        mem.store_unchecked(MREG_LY, new_ly);
-        new_ly
+    fn render_window(&mut self, mem: &Memory, lcdc: u8, ly: usize, line: &mut [u8]) {
+        let wy = mem.load_unchecked(MREG_WY) as usize;
+        let wx = mem.load_unchecked(MREG_WX) as usize;
+        if lcdc & LCDC_WINDOW_ENABLE == 0 || ly < wy || wx >= LCD_PIXELS_X + 7 {
+            return
+        }
+
+        // WX is offset by 7, values below 7 cut off the left edge of the window
+        let map = if lcdc & LCDC_WINDOW_MAP != 0 { TILE_MAP_1 } else { TILE_MAP_0 };
+        for (x, color) in line.iter_mut().enumerate().skip(wx.saturating_sub(7)) {
+            *color = Self::tile_map_color(mem, lcdc, map, x + 7 - wx, self.window_line);
+        }
+
+        // The window keeps its own line counter, which only advances on
+        // lines where it was drawn.
+        self.window_line += 1;
     }
 
-    fn set_mode(&mut self, flag: u8) {
-        let mut mem = self.mem.borrow_mut();
-        let stat = mem.load(MREG_STAT);
-        let mask = 0b11111100 | flag;
-        mem.store(MREG_STAT, stat & mask);
-        self.cycles = 0;
+    fn sprite_height(lcdc: u8) -> usize {
+        if lcdc & LCDC_OBJ_SIZE != 0 { 16 } else { 8 }
     }
 
-    fn update_mode0(&mut self) {
-        if self.cycles >= LCD_MODE0_CYCLES {
-            let ly = self.increase_line_count();
+    // Selects the sprites overlapping the current line, as done by the
+    // hardware during mode 2. Only the first ten in OAM order are kept, and
+    // they are drawn with the height they were selected with.
+    fn scan_oam(&mut self) {
+        let mem = self.mem.borrow();
+        let ly = mem.load_unchecked(MREG_LY) as usize;
+        let height = Self::sprite_height(mem.load_unchecked(MREG_LCDC));
+        self.sprite_height = height;
+
+        self.sprites.clear();
+        for i in 0..OAM_SPRITES {
+            let addr = OAM_START + i * OAM_ENTRY_BYTES;
+            let y = mem.load_unchecked(addr);
+            let top = y as usize;
+            if ly + 16 < top || ly + 16 >= top + height {
+                continue
+            }
 
-            self.set_mode(LCD_MODE1_FLAG);
+            self.sprites.push(Sprite {
+                y,
+                x: mem.load_unchecked(addr + 1),
+                tile: mem.load_unchecked(addr + 2),
+                flags: mem.load_unchecked(addr + 3),
+            });
+            if self.sprites.len() == SPRITES_PER_LINE {
+                break
+            }
         }
+
+        // On the DMG the sprite with the lowest X coordinate is drawn on
+        // top, ties go to the one first in OAM. The sort is stable, so the
+        // scan order settles ties.
+        self.sprites.sort_by_key(|s| s.x);
     }
 
-    fn update_mode1(&mut self) {
-        if self.cycles >= LCD_MODE1_CYCLES {
-            self.set_mode(LCD_MODE2_FLAG);
+    fn render_sprites(&self, mem: &Memory, ly: usize, bg: &[u8], line: &mut [Shade]) {
+        let height = self.sprite_height;
+        let obp0 = mem.load_unchecked(MREG_OBP0);
+        let obp1 = mem.load_unchecked(MREG_OBP1);
+
+        // Pixels already taken by an opaque pixel of a higher priority
+        // sprite. Only that sprite's BG priority flag counts, so a sprite
+        // behind the background also hides the sprites below it.
+        let mut taken = [false; LCD_PIXELS_X];
+
+        for sprite in self.sprites.iter() {
+            let mut row = ly + 16 - sprite.y as usize;
+            if sprite.flags & OBJ_FLIP_Y != 0 {
+                row = height - 1 - row;
+            }
+
+            // The lowest bit of the tile number is ignored for 8x16 sprites
+            let tile = if height == 16 { sprite.tile & 0xfe } else { sprite.tile } as usize;
+            let addr = TILE_DATA_UNSIGNED + tile * TILE_BYTES + row * 2;
+            let lo = mem.load_unchecked(addr);
+            let hi = mem.load_unchecked(addr + 1);
+            let palette = if sprite.flags & OBJ_PALETTE != 0 { obp1 } else { obp0 };
+
+            for px in 0..8 {
+                let x = sprite.x as usize + px;
+                if !(8..LCD_PIXELS_X + 8).contains(&x) {
+                    continue
+                }
+                let x = x - 8;
+
+                let bit = if sprite.flags & OBJ_FLIP_X != 0 { px } else { 7 - px };
+                let color = (((hi >> bit) & 1) << 1) | ((lo >> bit) & 1);
+                // Color 0 is transparent for sprites
+                if color == 0 || taken[x] {
+                    continue
+                }
+                taken[x] = true;
+                if sprite.flags & OBJ_BEHIND_BG != 0 && bg[x] != 0 {
+                    continue
+                }
+                line[x] = Shade::from_palette(palette, color);
+            }
         }
     }
 
-    fn update_mode2(&mut self) {
-        if self.cycles >= LCD_MODE2_CYCLES {
-            self.set_mode(LCD_MODE3_FLAG);
+    fn render_scanline(&mut self) {
+        let mem_ref = Rc::clone(&self.mem);
+        let mem = mem_ref.borrow();
+        let ly = mem.load_unchecked(MREG_LY) as usize;
+        if ly >= LCD_PIXELS_Y {
+            return
+        }
+        if ly == 0 {
+            self.window_line = 0;
+        }
+
+        let lcdc = mem.load_unchecked(MREG_LCDC);
+        let bgp = mem.load_unchecked(MREG_BGP);
+        let mut bg = [0u8; LCD_PIXELS_X];
+        let mut line = [Shade::Shade0; LCD_PIXELS_X];
+
+        // On the DMG, clearing the enable bit blanks both background and
+        // window to white
+        if lcdc & LCDC_BG_ENABLE != 0 {
+            Self::render_background(&mem, lcdc, ly, &mut bg);
+            self.render_window(&mem, lcdc, ly, &mut bg);
+            for (shade, &color) in line.iter_mut().zip(bg.iter()) {
+                *shade = Shade::from_palette(bgp, color);
+            }
         }
+
+        if lcdc & LCDC_OBJ_ENABLE != 0 {
+            self.render_sprites(&mem, ly, &bg, &mut line);
+        }
+
+        for (x, &shade) in line.iter().enumerate() {
+            self.set_pixel(x, ly, shade);
+        }
+    }
+
+    fn increase_line_count(&self) -> usize {
+        let mut mem = self.mem.borrow_mut();
+        let ly = (mem.load_unchecked(MREG_LY) as usize + 1) % LY_MAX;
+        mem.store_unchecked(MREG_LY, ly as u8);
+        ly
+    }
+
+    fn set_mode(&mut self, flag: u8) {
+        let mut mem = self.mem.borrow_mut();
+        let stat = mem.load_unchecked(MREG_STAT);
+        mem.store_unchecked(MREG_STAT, (stat & !STAT_MODE) | flag);
+        self.state = flag;
     }
 
-    fn update_mode3(&mut self) {
-        if self.cycles >= LCD_MODE3_CYCLES {
-            self.set_mode(LCD_MODE0_FLAG);
+    // Keeps the coincidence flag in STAT in sync with LY == LYC, and raises
+    // the STAT interrupt when any of its enabled conditions becomes true.
+    // All sources share one interrupt line, so a new condition does not
+    // trigger another interrupt while an earlier one still holds.
+    fn update_stat(&mut self) {
+        let mut mem = self.mem.borrow_mut();
+        let ly = mem.load_unchecked(MREG_LY);
+        let lyc = mem.load_unchecked(MREG_LYC);
+        let mut stat = mem.load_unchecked(MREG_STAT);
+        if ly == lyc {
+            stat |= STAT_COINCIDENCE;
+        } else {
+            stat &= !STAT_COINCIDENCE;
+        }
+        mem.store_unchecked(MREG_STAT, stat);
+
+        let line = match stat & STAT_MODE {
+            LCD_MODE0_FLAG => stat & STAT_MODE0_INTERRUPT != 0,
+            LCD_MODE1_FLAG => stat & STAT_MODE1_INTERRUPT != 0,
+            LCD_MODE2_FLAG => stat & STAT_MODE2_INTERRUPT != 0,
+            _ => false,
+        } || (stat & STAT_LYC_INTERRUPT != 0 && stat & STAT_COINCIDENCE != 0);
+
+        if line && !self.stat_line {
+            mem.set_interrupt_flag(INTERRUPT_LCD_STAT.flag);
         }
+        self.stat_line = line;
     }
 
+    // With the display off LY stays at 0 and STAT reports mode 0. Once
+    // turned back on, the first line starts over from the OAM scan, which
+    // like on hardware still reads as mode 0 until pixel transfer begins.
+    fn disable(&mut self) {
+        {
+            let mut mem = self.mem.borrow_mut();
+            let stat = mem.load_unchecked(MREG_STAT);
+            mem.store_unchecked(MREG_LY, 0);
+            mem.store_unchecked(MREG_STAT, stat & !STAT_MODE);
+        }
+        self.state = LCD_MODE2_FLAG;
+        self.cycles = 0;
+        self.stat_line = false;
+    }
+
+    // Each line takes 456 cycles. Lines 0-143 go through OAM scan (mode 2),
+    // pixel transfer (mode 3) and hblank (mode 0), lines 144-153 are
+    // vblank (mode 1).
     pub fn update(&mut self, cycles: usize) {
+        if !self.is_enabled() {
+            self.disable();
+            return
+        }
+
         self.cycles += cycles;
-        let state = self.mem.borrow().load(MREG_STAT) & 0b11;
-        match self.state {
-            LCD_MODE0_FLAG => self.update_mode0(),
-            LCD_MODE1_FLAG => self.update_mode1(),
-            LCD_MODE2_FLAG => self.update_mode2(),
-            LCD_MODE3_FLAG => self.update_mode3(),
+        loop {
+            match self.state {
+                LCD_MODE2_FLAG if self.cycles >= LCD_MODE2_CYCLES => {
+                    self.scan_oam();
+                    self.set_mode(LCD_MODE3_FLAG);
+                },
+                LCD_MODE3_FLAG if self.cycles >= LCD_MODE2_CYCLES + LCD_MODE3_CYCLES => {
+                    self.render_scanline();
+                    self.set_mode(LCD_MODE0_FLAG);
+                },
+                LCD_MODE0_FLAG if self.cycles >= LCD_LINE_CYCLES => {
+                    self.cycles -= LCD_LINE_CYCLES;
+                    if self.increase_line_count() == LCD_PIXELS_Y {
+                        self.set_mode(LCD_MODE1_FLAG);
+                        self.mem.borrow_mut().set_interrupt_flag(INTERRUPT_VBLANK.flag);
+                    } else {
+                        self.set_mode(LCD_MODE2_FLAG);
+                    }
+                },
+                LCD_MODE1_FLAG if self.cycles >= LCD_LINE_CYCLES => {
+                    self.cycles -= LCD_LINE_CYCLES;
+                    if self.increase_line_count() == 0 {
+                        self.set_mode(LCD_MODE2_FLAG);
+                    }
+                },
+                _ => break,
+            }
+            // Check after every step, so that conditions which only hold
+            // briefly within a long update still raise the interrupt
+            self.update_stat();
         }
+        self.update_stat();
     }
 }
 
@@ -140,4 +374,378 @@ mod tests {
         mem.borrow_mut().set_register_flag(MREG_LCDC, 0b1000_0000);
         assert!(lcd.is_enabled());
     }
+
+    fn setup_tile(mem: &Rc<RefCell<Memory>>, addr: usize) {
+        // Every row of the tile holds color numbers 0, 1, 2, 3, 0, 1, 2, 3
+        let mut mem = mem.borrow_mut();
+        for row in 0..8 {
+            mem.store(addr + row * 2, 0b0101_0101);
+            mem.store(addr + row * 2 + 1, 0b0011_0011);
+        }
+        mem.store(MREG_BGP, 0b1110_0100);
+    }
+
+    #[test]
+    fn test_palette_shade() {
+        assert_eq!(Shade::from_palette(0b1110_0100, 0), Shade::Shade0);
+        assert_eq!(Shade::from_palette(0b1110_0100, 3), Shade::Shade3);
+        assert_eq!(Shade::from_palette(0b0001_1011, 0), Shade::Shade3);
+    }
+
+    #[test]
+    fn test_render_background() {
+        let mem = Rc::new(RefCell::new(Memory::default()));
+        let mut lcd = LCD::new(Rc::clone(&mem));
+        setup_tile(&mem, TILE_DATA_UNSIGNED + TILE_BYTES);
+        mem.borrow_mut().store(TILE_MAP_0, 1);
+        mem.borrow_mut().store(MREG_LCDC, LCDC_ENABLE | LCDC_TILE_DATA | LCDC_BG_ENABLE);
+
+        lcd.render_scanline();
+        assert_eq!(lcd.data[0][0], Shade::Shade0);
+        assert_eq!(lcd.data[0][1], Shade::Shade1);
+        assert_eq!(lcd.data[0][2], Shade::Shade2);
+        assert_eq!(lcd.data[0][3], Shade::Shade3);
+
+        // Tile 0 is empty
+        assert_eq!(lcd.data[0][11], Shade::Shade0);
+    }
+
+    #[test]
+    fn test_render_signed_tile_data() {
+        let mem = Rc::new(RefCell::new(Memory::default()));
+        let mut lcd = LCD::new(Rc::clone(&mem));
+        setup_tile(&mem, TILE_DATA_SIGNED - TILE_BYTES);
+        mem.borrow_mut().store(TILE_MAP_1, 0xff);
+        mem.borrow_mut().store(MREG_LCDC, LCDC_ENABLE | LCDC_BG_MAP | LCDC_BG_ENABLE);
+
+        lcd.render_scanline();
+        assert_eq!(lcd.data[0][3], Shade::Shade3);
+    }
+
+    #[test]
+    fn test_render_scrolled_background() {
+        let mem = Rc::new(RefCell::new(Memory::default()));
+        let mut lcd = LCD::new(Rc::clone(&mem));
+        setup_tile(&mem, TILE_DATA_UNSIGNED + TILE_BYTES);
+        {
+            let mut mem = mem.borrow_mut();
+            mem.store(TILE_MAP_0 + TILE_MAP_WIDTH, 1);
+            mem.store(MREG_SCX, 2);
+            mem.store(MREG_SCY, 8);
+            mem.store(MREG_LCDC, LCDC_ENABLE | LCDC_TILE_DATA | LCDC_BG_ENABLE);
+        }
+
+        lcd.render_scanline();
+        assert_eq!(lcd.data[0][0], Shade::Shade2);
+        assert_eq!(lcd.data[0][1], Shade::Shade3);
+    }
+
+    #[test]
+    fn test_render_window() {
+        let mem = Rc::new(RefCell::new(Memory::default()));
+        let mut lcd = LCD::new(Rc::clone(&mem));
+        setup_tile(&mem, TILE_DATA_UNSIGNED + TILE_BYTES);
+        {
+            let mut mem = mem.borrow_mut();
+            mem.store(TILE_MAP_1, 1);
+            mem.store(MREG_WX, 7 + 80);
+            mem.store(MREG_LCDC, LCDC_ENABLE | LCDC_WINDOW_MAP | LCDC_WINDOW_ENABLE |
+                      LCDC_TILE_DATA | LCDC_BG_ENABLE);
+        }
+
+        lcd.render_scanline();
+        assert_eq!(lcd.data[0][79], Shade::Shade0);
+        assert_eq!(lcd.data[0][81], Shade::Shade1);
+        assert_eq!(lcd.data[0][83], Shade::Shade3);
+        assert_eq!(lcd.window_line, 1);
+    }
+
+    #[test]
+    fn test_window_line_counter() {
+        let mem = Rc::new(RefCell::new(Memory::default()));
+        let mut lcd = LCD::new(Rc::clone(&mem));
+        {
+            let mut mem = mem.borrow_mut();
+            mem.store(MREG_WY, 10);
+            mem.store(MREG_WX, 7);
+            mem.store(MREG_LCDC, LCDC_ENABLE | LCDC_WINDOW_ENABLE | LCDC_BG_ENABLE);
+        }
+
+        for ly in 0..20 {
+            mem.borrow_mut().store_unchecked(MREG_LY, ly);
+            // Hide the window for a few lines, which pauses its line counter
+            if ly == 15 {
+                mem.borrow_mut().store(MREG_WX, 200);
+            } else if ly == 18 {
+                mem.borrow_mut().store(MREG_WX, 7);
+            }
+            lcd.render_scanline();
+        }
+        assert_eq!(lcd.window_line, 7);
+
+        mem.borrow_mut().store_unchecked(MREG_LY, 0);
+        lcd.render_scanline();
+        assert_eq!(lcd.window_line, 0);
+    }
+
+    fn set_sprite(mem: &Rc<RefCell<Memory>>, index: usize, y: u8, x: u8, tile: u8, flags: u8) {
+        let addr = OAM_START + index * OAM_ENTRY_BYTES;
+        let mut mem = mem.borrow_mut();
+        mem.store(addr, y);
+        mem.store(addr + 1, x);
+        mem.store(addr + 2, tile);
+        mem.store(addr + 3, flags);
+    }
+
+    fn sprite_lcd(lcdc: u8) -> (Rc<RefCell<Memory>>, LCD) {
+        let mem = Rc::new(RefCell::new(Memory::default()));
+        let lcd = LCD::new(Rc::clone(&mem));
+        setup_tile(&mem, TILE_DATA_UNSIGNED + TILE_BYTES);
+        {
+            let mut mem = mem.borrow_mut();
+            mem.store(MREG_OBP0, 0b1110_0100);
+            mem.store(MREG_OBP1, 0b0001_1011);
+            mem.store(MREG_LCDC, LCDC_ENABLE | LCDC_OBJ_ENABLE | lcdc);
+        }
+        (mem, lcd)
+    }
+
+    fn render_line(mem: &Rc<RefCell<Memory>>, lcd: &mut LCD, ly: u8) {
+        mem.borrow_mut().store_unchecked(MREG_LY, ly);
+        lcd.scan_oam();
+        lcd.render_scanline();
+    }
+
+    #[test]
+    fn test_render_sprite() {
+        let (mem, mut lcd) = sprite_lcd(0);
+        set_sprite(&mem, 0, 16, 8 + 20, 1, 0);
+        set_sprite(&mem, 1, 16, 8 + 40, 1, OBJ_PALETTE | OBJ_FLIP_X);
+
+        render_line(&mem, &mut lcd, 0);
+        assert_eq!(lcd.data[0][20], Shade::Shade0);
+        assert_eq!(lcd.data[0][21], Shade::Shade1);
+        assert_eq!(lcd.data[0][23], Shade::Shade3);
+
+        // Flipped and drawn through OBP1
+        assert_eq!(lcd.data[0][41], Shade::Shade1);
+        assert_eq!(lcd.data[0][42], Shade::Shade2);
+        assert_eq!(lcd.data[0][47], Shade::Shade0);
+
+        // Sprites are hidden when disabled in LCDC
+        mem.borrow_mut().store(MREG_LCDC, LCDC_ENABLE);
+        render_line(&mem, &mut lcd, 0);
+        assert_eq!(lcd.data[0][23], Shade::Shade0);
+    }
+
+    #[test]
+    fn test_sprite_flip_y_tall() {
+        let (mem, mut lcd) = sprite_lcd(LCDC_OBJ_SIZE);
+        // Only the second tile of the 8x16 pair has data, row 0 of it
+        // holds color 3 in its leftmost pixel
+        {
+            let mut mem = mem.borrow_mut();
+            mem.store(TILE_DATA_UNSIGNED + 3 * TILE_BYTES, 0x80);
+            mem.store(TILE_DATA_UNSIGNED + 3 * TILE_BYTES + 1, 0x80);
+        }
+        set_sprite(&mem, 0, 16, 8, 2, 0);
+        set_sprite(&mem, 1, 16, 8 + 20, 3, OBJ_FLIP_Y);
+
+        render_line(&mem, &mut lcd, 8);
+        assert_eq!(lcd.data[8][0], Shade::Shade3);
+
+        // Flipped vertically, the bottom tile row ends up on line 7
+        render_line(&mem, &mut lcd, 7);
+        assert_eq!(lcd.data[7][20], Shade::Shade3);
+        assert_eq!(lcd.data[7][0], Shade::Shade0);
+    }
+
+    #[test]
+    fn test_sprite_height_change_after_scan() {
+        let (mem, mut lcd) = sprite_lcd(LCDC_OBJ_SIZE);
+        {
+            let mut mem = mem.borrow_mut();
+            mem.store(TILE_DATA_UNSIGNED + 3 * TILE_BYTES, 0x80);
+            mem.store(TILE_DATA_UNSIGNED + 3 * TILE_BYTES + 1, 0x80);
+        }
+        set_sprite(&mem, 0, 16, 8, 2, OBJ_FLIP_Y);
+
+        // Switching to 8x8 sprites after the scan still draws the line of
+        // the 8x16 sprite that was selected
+        mem.borrow_mut().store_unchecked(MREG_LY, 7);
+        lcd.scan_oam();
+        mem.borrow_mut().store(MREG_LCDC, LCDC_ENABLE | LCDC_OBJ_ENABLE);
+        lcd.render_scanline();
+        assert_eq!(lcd.data[7][0], Shade::Shade3);
+    }
+
+    #[test]
+    fn test_sprite_behind_background() {
+        let (mem, mut lcd) = sprite_lcd(LCDC_TILE_DATA | LCDC_BG_ENABLE);
+        mem.borrow_mut().store(MREG_BGP, 0);
+        mem.borrow_mut().store(TILE_MAP_0, 1);
+        set_sprite(&mem, 0, 16, 8, 1, OBJ_BEHIND_BG);
+        set_sprite(&mem, 1, 16, 8 + 8, 1, OBJ_BEHIND_BG);
+
+        render_line(&mem, &mut lcd, 0);
+        // Nonzero background colors cover the first sprite
+        assert_eq!(lcd.data[0][3], Shade::Shade0);
+        // Tile 0 is empty, so the second sprite is fully visible
+        assert_eq!(lcd.data[0][11], Shade::Shade3);
+    }
+
+    #[test]
+    fn test_sprite_behind_background_overlap() {
+        let (mem, mut lcd) = sprite_lcd(LCDC_TILE_DATA | LCDC_BG_ENABLE);
+        {
+            let mut mem = mem.borrow_mut();
+            mem.store(MREG_BGP, 0);
+            mem.store(TILE_MAP_0, 1);
+            mem.store(TILE_DATA_UNSIGNED + 2 * TILE_BYTES, 0xff);
+            mem.store(TILE_DATA_UNSIGNED + 2 * TILE_BYTES + 1, 0xff);
+        }
+        // Both at the same X, so the first in OAM has priority
+        set_sprite(&mem, 0, 16, 8, 1, OBJ_BEHIND_BG);
+        set_sprite(&mem, 1, 16, 8, 2, 0);
+
+        render_line(&mem, &mut lcd, 0);
+        // The winning sprite is behind the background, which therefore
+        // covers the sprite below it too
+        assert_eq!(lcd.data[0][3], Shade::Shade0);
+        // Where the winning sprite is transparent the other one shows
+        assert_eq!(lcd.data[0][0], Shade::Shade3);
+        assert_eq!(lcd.data[0][4], Shade::Shade3);
+    }
+
+    #[test]
+    fn test_sprite_x_priority() {
+        let (mem, mut lcd) = sprite_lcd(0);
+        mem.borrow_mut().store(TILE_DATA_UNSIGNED + 2 * TILE_BYTES, 0xff);
+        // Later in OAM, but further left, so it is drawn on top
+        set_sprite(&mem, 0, 16, 8 + 2, 2, 0);
+        set_sprite(&mem, 1, 16, 8, 1, 0);
+        // Same X as the first sprite, loses the tie on OAM index
+        set_sprite(&mem, 2, 16, 8 + 2, 1, OBJ_PALETTE);
+
+        render_line(&mem, &mut lcd, 0);
+        assert_eq!(lcd.data[0][2], Shade::Shade2);
+        assert_eq!(lcd.data[0][3], Shade::Shade3);
+        assert_eq!(lcd.data[0][8], Shade::Shade1);
+        assert_eq!(lcd.data[0][9], Shade::Shade1);
+    }
+
+    #[test]
+    fn test_sprites_per_line_limit() {
+        let (mem, mut lcd) = sprite_lcd(0);
+        for i in 0..12 {
+            set_sprite(&mem, i, 16, 8 + 8 * i as u8, 1, 0);
+        }
+        // Sprites on other lines do not count towards the limit
+        set_sprite(&mem, 12, 40, 8, 1, 0);
+
+        render_line(&mem, &mut lcd, 0);
+        assert_eq!(lcd.sprites.len(), SPRITES_PER_LINE);
+        assert_eq!(lcd.data[0][9 * 8 + 3], Shade::Shade3);
+        assert_eq!(lcd.data[0][10 * 8 + 3], Shade::Shade0);
+    }
+
+    fn enabled_lcd() -> (Rc<RefCell<Memory>>, LCD) {
+        let mem = Rc::new(RefCell::new(Memory::default()));
+        let lcd = LCD::new(Rc::clone(&mem));
+        mem.borrow_mut().store(MREG_LCDC, LCDC_ENABLE);
+        (mem, lcd)
+    }
+
+    fn mode(mem: &Rc<RefCell<Memory>>) -> u8 {
+        mem.borrow().load(MREG_STAT) & STAT_MODE
+    }
+
+    #[test]
+    fn test_mode_sequence() {
+        let (mem, mut lcd) = enabled_lcd();
+        lcd.update(LCD_LINE_CYCLES);
+        assert_eq!(mode(&mem), LCD_MODE2_FLAG);
+        assert_eq!(mem.borrow().load(MREG_LY), 1);
+        lcd.update(LCD_MODE2_CYCLES - 4);
+        assert_eq!(mode(&mem), LCD_MODE2_FLAG);
+        lcd.update(4);
+        assert_eq!(mode(&mem), LCD_MODE3_FLAG);
+        lcd.update(LCD_MODE3_CYCLES);
+        assert_eq!(mode(&mem), LCD_MODE0_FLAG);
+        assert_eq!(mem.borrow().load(MREG_LY), 1);
+        lcd.update(LCD_MODE0_CYCLES);
+        assert_eq!(mode(&mem), LCD_MODE2_FLAG);
+        assert_eq!(mem.borrow().load(MREG_LY), 2);
+    }
+
+    #[test]
+    fn test_vblank() {
+        let (mem, mut lcd) = enabled_lcd();
+        lcd.update(LCD_LINE_CYCLES * 144 - 4);
+        assert_eq!(mem.borrow().load(MREG_IF) & INTERRUPT_VBLANK.flag, 0);
+
+        lcd.update(4);
+        assert_eq!(mem.borrow().load(MREG_LY), 144);
+        assert_eq!(mode(&mem), LCD_MODE1_FLAG);
+        assert_ne!(mem.borrow().load(MREG_IF) & INTERRUPT_VBLANK.flag, 0);
+
+        lcd.update(LCD_LINE_CYCLES * 9);
+        assert_eq!(mem.borrow().load(MREG_LY), 153);
+        assert_eq!(mode(&mem), LCD_MODE1_FLAG);
+        lcd.update(LCD_LINE_CYCLES);
+        assert_eq!(mem.borrow().load(MREG_LY), 0);
+        assert_eq!(mode(&mem), LCD_MODE2_FLAG);
+    }
+
+    #[test]
+    fn test_lyc_interrupt() {
+        let (mem, mut lcd) = enabled_lcd();
+        mem.borrow_mut().store(MREG_LYC, 2);
+        mem.borrow_mut().store(MREG_STAT, STAT_LYC_INTERRUPT);
+
+        lcd.update(LCD_LINE_CYCLES);
+        assert_eq!(mem.borrow().load(MREG_STAT) & STAT_COINCIDENCE, 0);
+        assert_eq!(mem.borrow().load(MREG_IF) & INTERRUPT_LCD_STAT.flag, 0);
+
+        lcd.update(LCD_LINE_CYCLES);
+        assert_ne!(mem.borrow().load(MREG_STAT) & STAT_COINCIDENCE, 0);
+        assert_ne!(mem.borrow().load(MREG_IF) & INTERRUPT_LCD_STAT.flag, 0);
+
+        lcd.update(LCD_LINE_CYCLES);
+        assert_eq!(mem.borrow().load(MREG_STAT) & STAT_COINCIDENCE, 0);
+    }
+
+    #[test]
+    fn test_stat_interrupt_rising_edge() {
+        let (mem, mut lcd) = enabled_lcd();
+        mem.borrow_mut().store(MREG_STAT, STAT_MODE0_INTERRUPT | STAT_LYC_INTERRUPT);
+
+        // LY == LYC already holds at line 0, so entering hblank on the same
+        // line does not raise the interrupt again
+        lcd.update(4);
+        assert_ne!(mem.borrow().load(MREG_IF) & INTERRUPT_LCD_STAT.flag, 0);
+        mem.borrow_mut().store(MREG_IF, 0);
+        lcd.update(LCD_MODE2_CYCLES + LCD_MODE3_CYCLES);
+        assert_eq!(mode(&mem), LCD_MODE0_FLAG);
+        assert_eq!(mem.borrow().load(MREG_IF) & INTERRUPT_LCD_STAT.flag, 0);
+
+        lcd.update(LCD_LINE_CYCLES);
+        assert_ne!(mem.borrow().load(MREG_IF) & INTERRUPT_LCD_STAT.flag, 0);
+    }
+
+    #[test]
+    fn test_lcd_off() {
+        let (mem, mut lcd) = enabled_lcd();
+        lcd.update(LCD_LINE_CYCLES * 10 + LCD_MODE2_CYCLES);
+        assert_eq!(mem.borrow().load(MREG_LY), 10);
+
+        mem.borrow_mut().store(MREG_LCDC, 0);
+        lcd.update(4);
+        assert_eq!(mem.borrow().load(MREG_LY), 0);
+        assert_eq!(mode(&mem), LCD_MODE0_FLAG);
+
+        mem.borrow_mut().store(MREG_LCDC, LCDC_ENABLE);
+        lcd.update(LCD_LINE_CYCLES);
+        assert_eq!(mem.borrow().load(MREG_LY), 1);
+    }
 }
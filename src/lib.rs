@@ -1,7 +1,10 @@
 #[macro_use]
 extern crate error_chain;
 
+#[cfg(feature = "sdl")]
 extern crate sdl2;
+#[cfg(feature = "png")]
+extern crate png;
 
 mod errors {
     error_chain!{}
@@ -9,6 +12,12 @@ mod errors {
 
 pub mod constants;
 pub mod memory;
+pub mod cartridge;
+pub mod apu;
+pub mod audio;
+pub mod dma;
+pub mod joypad;
+pub mod serial;
 pub mod cpu;
 pub mod definition;
 pub mod instructions;
@@ -16,6 +25,8 @@ pub mod instruction_set;
 pub mod interrupts;
 pub mod operations;
 pub mod lcd;
+pub mod frontend;
+pub mod screenshot;
 pub mod debugger;
 pub mod emulator;
 pub mod timer;
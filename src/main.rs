@@ -1,10 +1,116 @@
 extern crate gameboy;
 
 use gameboy::emulator::Emulator;
+#[cfg(feature = "sdl")]
+use gameboy::frontend::{KeyMap, SdlFrontend};
+use gameboy::screenshot;
+use gameboy::serial::{CaptureLink, LinkEndpoint, Printer, TcpLink};
+use std::env::args;
 use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::rc::Rc;
+
+fn usage() -> ! {
+    println!("Usage: ./main [--boot-rom path] [--fast] [--screenshot-at-frame N] \
+              [--key-map path] [--record-audio path.wav] [--link-listen addr | --link-connect addr | \
+              --print-serial | --printer dir] [path to rom-file]");
+    process::exit(1);
+}
 
 fn main() {
-    let mut rom = File::open("/home/kalle/temp/boot.gb").unwrap();
-    let mut emu = Emulator::new(&mut rom);
-    emu.run();
+    let mut boot_rom_path = None;
+    let mut rom_path = None;
+    let mut screenshot_frame = None;
+    let mut key_map_path = None;
+    let mut record_audio_path = None;
+    let mut link: Option<Box<dyn LinkEndpoint>> = None;
+    let mut fast = false;
+
+    let mut args = args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--boot-rom" => boot_rom_path = Some(args.next().unwrap_or_else(|| usage())),
+            "--screenshot-at-frame" => {
+                let frame = args.next().and_then(|n| n.parse::<usize>().ok());
+                screenshot_frame = Some(frame.unwrap_or_else(|| usage()));
+            },
+            "--key-map" => key_map_path = Some(args.next().unwrap_or_else(|| usage())),
+            "--record-audio" => record_audio_path = Some(args.next().unwrap_or_else(|| usage())),
+            "--link-listen" => {
+                let addr = args.next().unwrap_or_else(|| usage());
+                link = Some(Box::new(TcpLink::listen(addr.as_str()).unwrap()));
+            },
+            "--link-connect" => {
+                let addr = args.next().unwrap_or_else(|| usage());
+                link = Some(Box::new(TcpLink::connect(addr.as_str()).unwrap()));
+            },
+            "--fast" => fast = true,
+            "--print-serial" => link = Some(Box::new(CaptureLink::with_echo())),
+            "--printer" => {
+                let dir = args.next().unwrap_or_else(|| usage());
+                let mut printer = Printer::with_output_dir(PathBuf::from(dir));
+                printer.set_log(Rc::new(|message: &str| println!("{}", message)));
+                link = Some(Box::new(printer));
+            },
+            _ if rom_path.is_none() => rom_path = Some(arg),
+            _ => usage(),
+        }
+    }
+
+    let rom_path = rom_path.unwrap_or_else(|| usage());
+    let rom_path = Path::new(&rom_path);
+    let mut rom = File::open(rom_path).unwrap();
+
+    let emu = match boot_rom_path {
+        Some(path) => {
+            let mut boot_rom = File::open(path).unwrap();
+            Emulator::with_boot_rom(&mut rom, &mut boot_rom)
+        },
+        None => Emulator::new(&mut rom),
+    };
+    let mut emu = emu.unwrap_or_else(|e| {
+        println!("Failed to load rom: {}", e);
+        process::exit(1);
+    });
+    emu.set_save_path(rom_path.with_extension("sav"));
+    if let Some(frame) = screenshot_frame {
+        let path = rom_path.with_extension(screenshot::default_extension());
+        emu.set_screenshot_at_frame(frame, path);
+    }
+    emu.set_fast_mode(fast);
+    if let Some(link) = link {
+        emu.set_link(link);
+    }
+    if let Some(path) = record_audio_path {
+        emu.record_audio(Path::new(&path)).unwrap();
+    }
+
+    // Without the sdl feature the emulator runs headless
+    #[cfg(feature = "sdl")]
+    {
+        let mut frontend = SdlFrontend::new().unwrap();
+        if let Some(path) = key_map_path {
+            frontend.set_key_map(KeyMap::load(Path::new(&path)).unwrap());
+        }
+        match frontend.open_audio() {
+            Ok(audio) => emu.set_audio_sink(Box::new(audio)),
+            Err(e) => println!("Failed to open audio, running without sound: {}", e),
+        }
+        emu.set_frontend(Box::new(frontend));
+    }
+    #[cfg(not(feature = "sdl"))]
+    {
+        if key_map_path.is_some() {
+            println!("Ignoring --key-map, built without the sdl feature");
+        }
+    }
+
+    // The save is written when the emulator is dropped
+    let result = emu.run();
+    drop(emu);
+    if let Err(e) = result {
+        println!("{}", e);
+        process::exit(1);
+    }
 }
@@ -1,36 +1,117 @@
 use constants::*;
+use apu::Apu;
+use cartridge::Cartridge;
+use dma::Dma;
+use interrupts::*;
+use joypad::Joypad;
+use serial::Serial;
 use errors::*;
 use std::fmt;
+use std::mem;
 use std::io::Read;
 use std::fs::File;
 
 const DEFAULT_RAM: usize = 0x10000; // 64 kB
+const ADDRESS_MASK: usize = 0xffff;
+
+// Bits of the I/O registers at 0xff00-0xff7f that are unused or write-only,
+// and therefore always read as 1 on the DMG.
+const IO_UNUSED_BITS: [u8; 0x80] = [
+    0xc0, 0x00, 0x7e, 0xff, 0x00, 0x00, 0x00, 0xf8,  // P1 .. TAC
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xe0,  // IF
+    0x80, 0x3f, 0x00, 0xff, 0xbf, 0xff, 0x3f, 0x00,  // NR10 .. NR22
+    0xff, 0xbf, 0x7f, 0xff, 0x9f, 0xff, 0xbf, 0xff,  // NR23 .. NR34
+    0xff, 0x00, 0x00, 0xbf, 0x00, 0x00, 0x70, 0xff,  // NR41 .. NR52
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,  // Wave RAM
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,  // LCDC .. BGP
+    0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff,  // OBP0 .. VBK
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,  // BOOT ..
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
+// I/O register contents left behind by the DMG boot rom
+const POST_BOOT_IO: [(usize, u8); 31] = [
+    (MREG_P1, 0xcf), (MREG_SC, 0x7e), (MREG_DIV, 0xab), (MREG_TAC, 0xf8),
+    (MREG_IF, 0xe1), (MREG_NR10, 0x80), (MREG_NR11, 0xbf), (MREG_NR12, 0xf3),
+    (MREG_NR13, 0xff), (MREG_NR14, 0xbf), (MREG_NR21, 0x3f), (MREG_NR23, 0xff),
+    (MREG_NR24, 0xbf), (MREG_NR30, 0x7f), (MREG_NR31, 0xff), (MREG_NR32, 0x9f),
+    (MREG_NR33, 0xff), (MREG_NR34, 0xbf), (MREG_NR41, 0xff), (MREG_NR44, 0xbf),
+    (MREG_NR50, 0x77), (MREG_NR51, 0xf3), (MREG_NR52, 0xf1), (MREG_LCDC, 0x91),
+    (MREG_STAT, 0x85), (MREG_DMA, 0xff), (MREG_BGP, 0xfc), (MREG_OBP0, 0xff),
+    (MREG_OBP1, 0xff), (MREG_BOOT, 0x01), (MREG_IE, 0x00),
+];
+
+const BOOT_ROM_SIZE: usize = 0x100;
+
+// Bits of STAT set by the LCD controller, not by writes from the CPU
+const STAT_READ_ONLY_BITS: u8 = 0b0000_0111;
 
 pub struct Memory {
-    size: usize,
     mem: [u8; DEFAULT_RAM],
-    pub interrupts: bool,
+    boot_rom: Option<Vec<u8>>,
+    pub cartridge: Option<Cartridge>,
+    dma: Dma,
+    joypad: Joypad,
+    serial: Serial,
+    apu: Apu,
+    divider_reset: bool,
 }
 
 impl Memory {
-    pub fn new(size: usize) -> Memory {
-        // TODO: Set actual size
+    pub fn new() -> Memory {
         Memory {
-            size,
             mem: [0u8; DEFAULT_RAM],
-            interrupts: true,
+            boot_rom: None,
+            cartridge: None,
+            dma: Dma::new(),
+            joypad: Joypad::new(),
+            serial: Serial::new(),
+            apu: Apu::default(),
+            divider_reset: false,
         }
     }
 
+    // Addresses wrap around the 16 bit address space, like the CPU's
+    // address bus does.
     pub fn store(&mut self, addr: usize, value: u8) {
-        assert!(addr < self.size,
-                "Attempt to store outside of memory bound. {:04x} > {:04x}",
-                addr, self.size);
-        self.mem[addr] = match addr {
-            MREG_DIV => 0,
-            MREG_LY => 0,
-            _ => value,
-        };
+        let addr = addr & ADDRESS_MASK;
+        if !self.is_accessible(addr) {
+            return
+        }
+
+        match (addr, self.cartridge.as_mut()) {
+            (0x0000..=ROM_END, Some(cart)) |
+            (EXT_RAM_START..=EXT_RAM_END, Some(cart)) => cart.write(addr, value),
+            (ECHO_START..=ECHO_END, _) => self.mem[addr - (ECHO_START - WRAM_START)] = value,
+            (UNUSABLE_START..=UNUSABLE_END, _) => (),
+            (MREG_P1, _) => self.joypad.write(value),
+            (MREG_SB, _) | (MREG_SC, _) => self.serial.write(addr, value),
+            (MREG_NR10..=MREG_WAV15, _) => self.apu.write(addr, value),
+            (MREG_DIV, _) => self.reset_divider(),
+            (MREG_LY, _) => self.mem[addr] = 0,
+            (MREG_STAT, _) => {
+                let stat = self.mem[addr] & STAT_READ_ONLY_BITS;
+                self.mem[addr] = (value & !STAT_READ_ONLY_BITS) | stat;
+            },
+            (MREG_IF, _) => self.mem[addr] = value & 0x1f,
+            (MREG_DMA, _) => {
+                self.mem[addr] = value;
+                self.dma.start(value);
+            },
+            (MREG_BOOT, _) => {
+                // Once unmapped, the boot rom stays unmapped until reset
+                if value != 0 {
+                    self.boot_rom = None;
+                }
+            },
+            _ => self.mem[addr] = value,
+        }
     }
 
     pub fn store_unchecked(&mut self, addr: usize, value: u8) {
@@ -38,10 +119,53 @@ impl Memory {
     }
 
     pub fn load(&self, addr: usize) -> u8 {
-        assert!(addr < self.size,
-                "Attempt to load outside of memory bound. {:04x} > {:04x}",
-                addr, self.size);
-        self.mem[addr]
+        let addr = addr & ADDRESS_MASK;
+        if !self.is_accessible(addr) {
+            return 0xff
+        }
+        self.read(addr)
+    }
+
+    // While an OAM DMA transfer is running the CPU can only reach HRAM, the
+    // interrupt enable register and DMA itself, which restarts the transfer
+    fn is_accessible(&self, addr: usize) -> bool {
+        !self.dma.is_active() || addr == MREG_DMA ||
+            (HRAM_START..=MREG_IE).contains(&addr)
+    }
+
+    // Clears DIV along with the internal counter behind it, which the timer
+    // picks up on its next increase
+    pub fn reset_divider(&mut self) {
+        self.mem[MREG_DIV] = 0;
+        self.divider_reset = true;
+    }
+
+    pub fn take_divider_reset(&mut self) -> bool {
+        mem::replace(&mut self.divider_reset, false)
+    }
+
+    pub fn is_dma_active(&self) -> bool {
+        self.dma.is_active()
+    }
+
+    fn read(&self, addr: usize) -> u8 {
+        if let Some(ref boot_rom) = self.boot_rom {
+            if addr <= BOOT_ROM_END {
+                return *boot_rom.get(addr).unwrap_or(&0xff)
+            }
+        }
+
+        match (addr, self.cartridge.as_ref()) {
+            (0x0000..=ROM_END, Some(cart)) |
+            (EXT_RAM_START..=EXT_RAM_END, Some(cart)) => cart.read(addr),
+            (ECHO_START..=ECHO_END, _) => self.mem[addr - (ECHO_START - WRAM_START)],
+            (UNUSABLE_START..=UNUSABLE_END, _) => 0x00,
+            (MREG_P1, _) => self.joypad.read() | IO_UNUSED_BITS[addr - IO_START],
+            (MREG_SB, _) | (MREG_SC, _) => self.serial.read(addr) | IO_UNUSED_BITS[addr - IO_START],
+            (MREG_NR10..=MREG_WAV15, _) => self.apu.read(addr) | IO_UNUSED_BITS[addr - IO_START],
+            (IO_START..=IO_END, _) => self.mem[addr] | IO_UNUSED_BITS[addr - IO_START],
+            _ => self.mem[addr],
+        }
     }
 
     pub fn load_unchecked(&self, addr: usize) -> u8 {
@@ -71,19 +195,88 @@ impl Memory {
         self.mem[reg_addr] &= !flag;
     }
 
+    // Requests are latched in IF whether or not the CPU takes interrupts
     pub fn set_interrupt_flag(&mut self, flag: u8) {
-        if !self.interrupts {
-            return
-        }
         self.set_register_flag(MREG_IF, flag);
     }
 
-    pub fn set_interrupts_enabled(&mut self, val: bool) {
-        self.interrupts = val
+    pub fn apu(&self) -> &Apu {
+        &self.apu
+    }
+
+    pub fn apu_mut(&mut self) -> &mut Apu {
+        &mut self.apu
+    }
+
+    pub fn joypad_mut(&mut self) -> &mut Joypad {
+        &mut self.joypad
+    }
+
+    pub fn serial_mut(&mut self) -> &mut Serial {
+        &mut self.serial
+    }
+
+    pub fn update(&mut self, cycles: usize) {
+        if self.joypad.take_interrupt() {
+            self.set_interrupt_flag(INTERRUPT_JOYPAD.flag);
+        }
+
+        self.serial.tick(cycles);
+        if self.serial.take_interrupt() {
+            self.set_interrupt_flag(INTERRUPT_SERIAL.flag);
+        }
+
+        self.apu.tick(cycles);
+        for i in self.dma.tick(cycles) {
+            let value = self.read(self.dma.source_addr(i));
+            self.mem[OAM_START + i] = value;
+        }
+
+        if let Some(ref mut cart) = self.cartridge {
+            cart.tick(cycles);
+        }
+    }
+
+    // Overlays 0x0000-0x00ff until a non-zero write to MREG_BOOT
+    pub fn map_boot_rom(&mut self, boot_rom: Vec<u8>) {
+        self.boot_rom = Some(boot_rom);
+    }
+
+    pub fn is_boot_rom_mapped(&self) -> bool {
+        self.boot_rom.is_some()
+    }
+
+    pub fn load_boot_rom(&mut self, boot_rom: &mut File) -> Result<usize> {
+        let mut data = Vec::new();
+        let bytes_read = boot_rom.read_to_end(&mut data).chain_err(|| "Failed to read boot rom")?;
+        if bytes_read != BOOT_ROM_SIZE {
+            bail!("Expected a {} byte boot rom, got {} bytes", BOOT_ROM_SIZE, bytes_read);
+        }
+        self.map_boot_rom(data);
+        Ok(bytes_read)
+    }
+
+    // Puts the I/O registers in the state the boot rom leaves them in,
+    // for starting directly at the cartridge entry point.
+    pub fn init_post_boot(&mut self) {
+        self.boot_rom = None;
+        // Like the boot rom, turn on sound before setting up the channels
+        self.apu.write(MREG_NR52, 0x80);
+        for &(addr, value) in POST_BOOT_IO.iter() {
+            match addr {
+                MREG_P1 => self.joypad.write(value),
+                MREG_SB | MREG_SC => self.serial.write(addr, value),
+                MREG_NR10..=MREG_WAV15 => self.apu.write(addr, value),
+                _ => self.store_unchecked(addr, value),
+            }
+        }
     }
 
     pub fn load_rom(&mut self, rom: &mut File) -> Result<usize> {
-        rom.read(&mut self.mem).chain_err(|| "Failed to read rom")
+        let mut data = Vec::new();
+        let bytes_read = rom.read_to_end(&mut data).chain_err(|| "Failed to read rom")?;
+        self.cartridge = Some(Cartridge::new(data)?);
+        Ok(bytes_read)
     }
 
     pub fn clear(&mut self) {
@@ -93,19 +286,20 @@ impl Memory {
 
 impl Default for Memory {
     fn default() -> Memory {
-        Memory::new(DEFAULT_RAM)
+        Memory::new()
     }
 }
 
 impl fmt::Debug for Memory {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Memory[{}]", self.size)
+        write!(f, "Memory[{}]", DEFAULT_RAM)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use joypad::Button;
     use std::fs::File;
 
     #[test]
@@ -134,6 +328,174 @@ mod tests {
         assert_eq!(mem.load(0x02), 0xff);
     }
 
+    #[test]
+    fn test_cartridge_banking() {
+        let mut rom = vec![0u8; 4 * ROM_BANK_SIZE];
+        rom[HEADER_CARTRIDGE_TYPE] = 0x01;
+        rom[HEADER_ROM_SIZE] = 0x01;
+        rom[3 * ROM_BANK_SIZE] = 0x33;
+
+        let mut mem = Memory {
+            cartridge: Some(Cartridge::new(rom).unwrap()),
+            ..Default::default()
+        };
+        mem.store(0x2000, 0x03);
+
+        assert_eq!(mem.load(0x2000), 0x00);
+        assert_eq!(mem.load(0x4000), 0x33);
+    }
+
+    #[test]
+    fn test_echo_ram() {
+        let mut mem = Memory::default();
+        mem.store(0xc123, 0x12);
+        assert_eq!(mem.load(0xe123), 0x12);
+
+        mem.store(0xfdff, 0x34);
+        assert_eq!(mem.load(0xddff), 0x34);
+    }
+
+    #[test]
+    fn test_unusable_region() {
+        let mut mem = Memory::default();
+        mem.store(0xfea0, 0x12);
+        assert_eq!(mem.load(0xfea0), 0x00);
+    }
+
+    #[test]
+    fn test_io_unused_bits() {
+        let mut mem = Memory::default();
+        mem.store(MREG_IF, 0x01);
+        assert_eq!(mem.load(MREG_IF), 0xe1);
+
+        mem.store(MREG_TAC, 0x05);
+        assert_eq!(mem.load(MREG_TAC), 0xfd);
+
+        // Write-only registers read back as all ones
+        mem.store(MREG_NR13, 0x12);
+        assert_eq!(mem.load(MREG_NR13), 0xff);
+        assert_eq!(mem.load(0xff4c), 0xff);
+    }
+
+    #[test]
+    fn test_stat_read_only_bits() {
+        let mut mem = Memory::default();
+        mem.store_unchecked(MREG_STAT, LCD_MODE3_FLAG);
+        mem.store(MREG_STAT, 0b0111_1000);
+        assert_eq!(mem.load(MREG_STAT), 0b1111_1011);
+    }
+
+    #[test]
+    fn test_address_wraps() {
+        let mut mem = Memory::default();
+        mem.store(0x10000 + 0xc000, 0x12);
+        assert_eq!(mem.load(0xc000), 0x12);
+        assert_eq!(mem.load(0x1c000), 0x12);
+    }
+
+    #[test]
+    fn test_boot_rom_unmap() {
+        let mut mem = Memory::default();
+        mem.store(0x0000, 0x11);
+        mem.map_boot_rom(vec![0x31; 0x100]);
+        assert_eq!(mem.load(0x0000), 0x31);
+        assert_eq!(mem.load(0x0100), 0x00);
+
+        mem.store(MREG_BOOT, 0x01);
+        assert!(!mem.is_boot_rom_mapped());
+        assert_eq!(mem.load(0x0000), 0x11);
+        assert_eq!(mem.load(MREG_BOOT), 0xff);
+    }
+
+    #[test]
+    fn test_init_post_boot() {
+        let mut mem = Memory::default();
+        mem.map_boot_rom(vec![0x31; 0x100]);
+        mem.init_post_boot();
+        assert!(!mem.is_boot_rom_mapped());
+        assert_eq!(mem.load(MREG_LCDC), 0x91);
+        assert_eq!(mem.load(MREG_BGP), 0xfc);
+        assert_eq!(mem.load(MREG_NR52), 0xf1);
+    }
+
+    #[test]
+    fn test_oam_dma() {
+        let mut mem = Memory::default();
+        for i in 0..0xa0 {
+            mem.store(0xc100 + i, i as u8);
+        }
+        mem.store(HRAM_START, 0x12);
+        mem.store(MREG_DMA, 0xc1);
+        assert!(mem.is_dma_active());
+
+        // Only HRAM and IE are reachable during the transfer
+        assert_eq!(mem.load(0xc100), 0xff);
+        assert_eq!(mem.load(HRAM_START), 0x12);
+        mem.store(0xc100, 0x34);
+        mem.store(HRAM_START + 1, 0x56);
+        assert_eq!(mem.load(HRAM_START + 1), 0x56);
+        mem.store(MREG_IE, 0x05);
+        assert_eq!(mem.load(MREG_IE), 0x05);
+
+        mem.update(4 * 0x50);
+        assert_eq!(mem.load_unchecked(OAM_START + 0x4f), 0x4f);
+        assert_eq!(mem.load_unchecked(OAM_START + 0x50), 0x00);
+
+        mem.update(4 * 0x50);
+        assert!(!mem.is_dma_active());
+        assert_eq!(mem.load(OAM_END), 0x9f);
+        assert_eq!(mem.load(0xc100), 0x00);
+        assert_eq!(mem.load(MREG_DMA), 0xc1);
+    }
+
+    #[test]
+    fn test_oam_dma_restart() {
+        let mut mem = Memory::default();
+        for i in 0..0xa0 {
+            mem.store(0xc100 + i, 0x11);
+            mem.store(0xc200 + i, 0x22);
+        }
+        mem.store(MREG_DMA, 0xc1);
+        mem.update(4 * 0x50);
+        assert_eq!(mem.load_unchecked(OAM_START), 0x11);
+
+        // The new transfer starts over from the first byte
+        mem.store(MREG_DMA, 0xc2);
+        assert_eq!(mem.load(MREG_DMA), 0xc2);
+        mem.update(4 * 0x50);
+        assert!(mem.is_dma_active());
+        assert_eq!(mem.load_unchecked(OAM_START), 0x22);
+        assert_eq!(mem.load_unchecked(OAM_START + 0x50), 0x00);
+
+        mem.update(4 * 0x50);
+        assert!(!mem.is_dma_active());
+        assert_eq!(mem.load(OAM_END), 0x22);
+    }
+
+    #[test]
+    fn test_joypad() {
+        let mut mem = Memory::default();
+        mem.store(MREG_P1, 0x10);
+        mem.joypad_mut().set_button(Button::A, true);
+        assert_eq!(mem.load(MREG_P1), 0xde);
+
+        mem.update(4);
+        assert_eq!(mem.load(MREG_IF) & INTERRUPT_JOYPAD.flag, INTERRUPT_JOYPAD.flag);
+    }
+
+    #[test]
+    fn test_serial_transfer() {
+        let mut mem = Memory::default();
+        mem.store(MREG_SB, 0x42);
+        mem.store(MREG_SC, 0x81);
+        assert_eq!(mem.load(MREG_SC), 0xff);
+
+        mem.update(4096);
+        assert_eq!(mem.load(MREG_SB), 0xff);
+        assert_eq!(mem.load(MREG_SC), 0x7f);
+        assert_eq!(mem.load(MREG_IF) & INTERRUPT_SERIAL.flag, INTERRUPT_SERIAL.flag);
+    }
+
     #[test]
     fn test_write_to_reset() {
         let mut mem = Memory::default();
@@ -141,5 +503,7 @@ mod tests {
         mem.store(MREG_DIV, 0xbb);
 
         assert_eq!(mem.load(MREG_DIV), 0);
+        assert!(mem.take_divider_reset());
+        assert!(!mem.take_divider_reset());
     }
 }
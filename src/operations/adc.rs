@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct AddCarry;
 
 impl Execute for AddCarry {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let src = instruction.get_operand(1)?;
 
         match *src {
@@ -16,52 +16,40 @@ impl Execute for AddCarry {
                 let a = cpu.reg[REG_A];
                 let b = cpu.reg[r];
 
-                let mut val = a.wrapping_add(b);
-
-                if cpu.flag_is_set(FLAG_C) {
-                    val = val.wrapping_add(1);
-                }
+                let carry = cpu.flag_is_set(FLAG_C) as u8;
+                let val = a.wrapping_add(b).wrapping_add(carry);
 
                 cpu.reg[REG_A] = val;
 
                 cpu.flag_cond(FLAG_Z, val == 0);
                 cpu.clear_flag(FLAG_N);
-                cpu.set_half_carry(a as usize, b as usize);
-                cpu.set_carry(a as usize, b as usize);
+                cpu.set_add_carries(a, b, carry);
             },
             Operand::RegisterPairAddr(h, l) => {
                 let addr = cpu.read_reg_addr(h, l);
                 let a = cpu.reg[REG_A];
                 let b = cpu.load_mem(addr);
 
-                let mut val = a.wrapping_add(b);
-
-                if cpu.flag_is_set(FLAG_C) {
-                    val = val.wrapping_add(1);
-                }
+                let carry = cpu.flag_is_set(FLAG_C) as u8;
+                let val = a.wrapping_add(b).wrapping_add(carry);
 
                 cpu.reg[REG_A] = val;
 
                 cpu.flag_cond(FLAG_Z, val == 0);
                 cpu.clear_flag(FLAG_N);
-                cpu.set_half_carry(a as usize, b as usize);
-                cpu.set_carry(a as usize, b as usize);
+                cpu.set_add_carries(a, b, carry);
             },
             Operand::Immediate(BYTE) => {
                 let a = cpu.reg[REG_A];
                 let b = instruction.get_immediate_u8()?;
-                let mut val = a.wrapping_add(b);
-
-                if cpu.flag_is_set(FLAG_C) {
-                    val = val.wrapping_add(1);
-                }
+                let carry = cpu.flag_is_set(FLAG_C) as u8;
+                let val = a.wrapping_add(b).wrapping_add(carry);
 
                 cpu.reg[REG_A] = val;
 
                 cpu.flag_cond(FLAG_Z, val == 0);
                 cpu.clear_flag(FLAG_N);
-                cpu.set_half_carry(a as usize, b as usize);
-                cpu.set_carry(a as usize, b as usize);
+                cpu.set_add_carries(a, b, carry);
 
             },
             _ => {
@@ -69,7 +57,7 @@ impl Execute for AddCarry {
                 println!("UNEXPECTED OPERANDS {}", src);
             },
         };
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -170,13 +158,24 @@ mod tests {
     fn test_adc_regpair_addr_to_a() {
         for carry in 0..2 {
             let mut cpu = test_cpu();
-            cpu.store_mem(0xff22, 0x11);
+            cpu.store_mem(0xff82, 0x11);
             cpu.reg[REG_A] = 0x9a;
             cpu.reg[REG_H] = 0xff;
-            cpu.reg[REG_L] = 0x22;
+            cpu.reg[REG_L] = 0x82;
             cpu.flag_cond(FLAG_C, carry == 1);
             execute_instruction(&mut cpu, 0x8e, None);
             assert_eq!(cpu.reg[REG_A], 0xab + carry);
         }
     }
+
+    #[test]
+    fn test_adc_half_carry_from_carry() {
+        let mut cpu = test_cpu();
+        cpu.reg[REG_A] = 0x0f;
+        cpu.reg[REG_B] = 0x00;
+        cpu.set_flag(FLAG_C);
+        execute_instruction(&mut cpu, 0x88, None);
+        assert_eq!(cpu.reg[REG_A], 0x10);
+        assert_eq!(cpu.flag, 0b0010_0000);
+    }
 }
@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct Add;
 
 impl Execute for Add {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let dst = instruction.get_operand(0)?;
         let src = instruction.get_operand(1)?;
 
@@ -21,8 +21,7 @@ impl Execute for Add {
 
                 cpu.flag_cond(FLAG_Z, val == 0);
                 cpu.clear_flag(FLAG_N);
-                cpu.set_half_carry(op1 as usize, op2 as usize);
-                cpu.set_carry(op1 as usize, op2 as usize);
+                cpu.set_add_carries(op1, op2, 0);
             },
             (&Operand::Register(r), &Operand::RegisterPairAddr(h, l)) => {
                 let op1 = cpu.reg[r];
@@ -33,8 +32,7 @@ impl Execute for Add {
 
                 cpu.flag_cond(FLAG_Z, val == 0);
                 cpu.clear_flag(FLAG_N);
-                cpu.set_half_carry(op1 as usize, op2 as usize);
-                cpu.set_carry(op1 as usize, op2 as usize);
+                cpu.set_add_carries(op1, op2, 0);
             },
             (&Operand::Register(r), &Operand::Immediate(BYTE)) => {
                 let op1 = cpu.reg[r];
@@ -44,8 +42,7 @@ impl Execute for Add {
 
                 cpu.flag_cond(FLAG_Z, val == 0);
                 cpu.clear_flag(FLAG_N);
-                cpu.set_half_carry(op1 as usize, op2 as usize);
-                cpu.set_carry(op1 as usize, op2 as usize);
+                cpu.set_add_carries(op1, op2, 0);
             },
             (&Operand::RegisterPair(h1, l1), &Operand::RegisterPair(h2, l2)) => {
                 let op1 = cpu.read_reg_short(h1, l1);
@@ -53,10 +50,9 @@ impl Execute for Add {
                 cpu.store_reg_short(h1, l1, op1.wrapping_add(op2));
 
                 cpu.clear_flag(FLAG_N);
-
-                // 16 bit carry
-                cpu.set_half_carry((op1 >> 8) as usize, (op2 >> 8) as usize);
-                cpu.set_carry((op1 >> 8) as usize, (op2 >> 8) as usize);
+                // Carries out of bit 11 and bit 15
+                cpu.flag_cond(FLAG_H, (op1 & 0xfff) + (op2 & 0xfff) > 0xfff);
+                cpu.flag_cond(FLAG_C, op1 as u32 + op2 as u32 > 0xffff);
             },
             (&Operand::RegisterPair(h, l), &Operand::SP) => {
                 let op1 = cpu.read_reg_short(h, l);
@@ -64,24 +60,25 @@ impl Execute for Add {
                 cpu.store_reg_short(h, l, op1.wrapping_add(op2));
 
                 cpu.clear_flag(FLAG_N);
-                cpu.set_half_carry((op1 >> 8) as usize, (op2 >> 8) as usize);
-                cpu.set_carry((op1 >> 8) as usize, (op2 >> 8) as usize);
+                cpu.flag_cond(FLAG_H, (op1 & 0xfff) + (op2 & 0xfff) > 0xfff);
+                cpu.flag_cond(FLAG_C, op1 as u32 + op2 as u32 > 0xffff);
             },
             (&Operand::SP, &Operand::Offset(BYTE)) => {
                 let op1 = cpu.sp;
                 let op2 = instruction.get_immediate_i8()?;
 
-                // TODO: Check this. Danger danger
-                cpu.sp = (op1 as i32 + op2 as i32) as u16;
+                cpu.sp = op1.wrapping_add(op2 as u16);
                 cpu.clear_flag(FLAG_Z);
                 cpu.clear_flag(FLAG_N);
+                // The carries come from adding the offset to the low byte
+                cpu.set_add_carries(op1 as u8, op2 as u8, 0);
             },
             _ => {
                 // TODO: Add error here
                 println!("UNEXPECTED OPERANDS {} {}", dst, src);
             },
         };
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -164,10 +161,10 @@ mod tests {
     #[test]
     fn test_add_regpair_addr_to_a() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0x11);
+        cpu.store_mem(0xff82, 0x11);
         cpu.reg[REG_A] = 0x9a;
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0x86, None);
         assert_eq!(cpu.reg[REG_A], 0xab);
     }
@@ -235,4 +232,18 @@ mod tests {
         assert_eq!(cpu.reg[REG_H], 0x01);
         assert_eq!(cpu.reg[REG_L], 0x05);
     }
+
+    #[test]
+    fn test_add_offset_to_sp() {
+        let mut cpu = test_cpu();
+        cpu.sp = 0x00f8;
+        execute_instruction(&mut cpu, 0xe8, Some(0x0a));
+        assert_eq!(cpu.sp, 0x0102);
+        assert_eq!(cpu.flag, 0b0011_0000);
+
+        cpu.sp = 0x1000;
+        execute_instruction(&mut cpu, 0xe8, Some(0xfe));
+        assert_eq!(cpu.sp, 0x0ffe);
+        assert_eq!(cpu.flag, 0b0000_0000);
+    }
 }
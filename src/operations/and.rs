@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct And;
 
 impl Execute for And {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let src = instruction.get_operand(0)?;
 
         match *src {
@@ -33,7 +33,7 @@ impl Execute for And {
         cpu.clear_flag(FLAG_N);
         cpu.set_flag(FLAG_H);
         cpu.clear_flag(FLAG_C);
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -98,10 +98,10 @@ mod tests {
     #[test]
     fn test_and_regpair_addr_with_a() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0b0011_1000);
+        cpu.store_mem(0xff82, 0b0011_1000);
         cpu.reg[REG_A] = 0b0001_1100;
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0xa6, None);
         assert_eq!(cpu.reg[REG_A], 0b0001_1000);
         assert_eq!(cpu.flag, 0b0010_0000);
@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct Bit;
 
 impl Execute for Bit {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
 
         let bit = instruction.get_operand(0)?;
         let src = instruction.get_operand(1)?;
@@ -37,7 +37,7 @@ impl Execute for Bit {
                 println!("UNEXPECTED OPERAND {}", src);
             },
         };
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -81,12 +81,12 @@ mod tests {
     fn test_bit_regpair_addr() {
         for bit in 0..8 {
             let mut cpu = test_cpu();
-            cpu.store_mem(0xff22, 1u8 << bit);
+            cpu.store_mem(0xff82, 1u8 << bit);
             cpu.reg[REG_H] = 0xff;
-            cpu.reg[REG_L] = 0x22;
+            cpu.reg[REG_L] = 0x82;
             execute_instruction(&mut cpu, 0xcb46 + 8 * bit, None);
             assert_eq!(cpu.flag, 0b0010_0000);
-            cpu.store_mem(0xff22, 0);
+            cpu.store_mem(0xff82, 0);
             execute_instruction(&mut cpu, 0xcb46 + 8 * bit, None);
             assert_eq!(cpu.flag, 0b1010_0000);
         }
@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct Call;
 
 impl Execute for Call {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let op1 = instruction.get_operand(0)?;
         let op2 = instruction.get_operand(1)?;
 
@@ -28,15 +28,15 @@ impl Execute for Call {
 
         if should_call {
             let next_addr = cpu.pc.wrapping_add(len);
-            cpu.stack_push((next_addr & 0xff) as u8);
-            cpu.stack_push((next_addr >> 8) as u8);
+            cpu.internal_cycle();
+            cpu.stack_push_u16(next_addr);
             cpu.pc = instruction.get_immediate_u16()?;
-        }
 
-        // Accommodate for this instruction
-        cpu.pc = cpu.pc.wrapping_sub(len);
+            // Accommodate for this instruction
+            cpu.pc = cpu.pc.wrapping_sub(len);
+        }
 
-        Ok(())
+        Ok(should_call)
     }
 }
 
@@ -58,8 +58,8 @@ mod tests {
         cpu.sp = 0x1122;
         execute_instruction(&mut cpu, 0xcd, Some(0xff22));
         assert_eq!(cpu.pc, 0xff22);
-        assert_eq!(cpu.load_mem(0x1120), 0x22);
-        assert_eq!(cpu.load_mem(0x1121), 0x36);
+        assert_eq!(cpu.load_mem(0x1121), 0x22);
+        assert_eq!(cpu.load_mem(0x1120), 0x36);
     }
 
     #[test]
@@ -78,8 +78,8 @@ mod tests {
             cpu.flag_cond(f, s);
             execute_instruction(&mut cpu, c, Some(0xff22));
             assert_eq!(cpu.pc, 0xff22);
-            assert_eq!(cpu.load_mem(0x1120), 0x22);
-            assert_eq!(cpu.load_mem(0x1121), 0x36);
+            assert_eq!(cpu.load_mem(0x1121), 0x22);
+            assert_eq!(cpu.load_mem(0x1120), 0x36);
         }
     }
 }
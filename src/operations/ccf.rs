@@ -7,12 +7,12 @@ use operations::Execute;
 pub struct ComplementCarryFlag;
 
 impl Execute for ComplementCarryFlag {
-    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let is_carry = cpu.flag_is_set(FLAG_C);
         cpu.clear_flag(FLAG_N);
         cpu.clear_flag(FLAG_H);
         cpu.flag_cond(FLAG_C, !is_carry);
-        Ok(())
+        Ok(true)
     }
 }
 
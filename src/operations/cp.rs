@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct Compare;
 
 impl Execute for Compare {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let src = instruction.get_operand(0)?;
 
         let a = cpu.reg[REG_A];
@@ -28,9 +28,8 @@ impl Execute for Compare {
 
         cpu.flag_cond(FLAG_Z, a == b);
         cpu.set_flag(FLAG_N);
-        cpu.set_half_carry(a as usize, b as usize);
-        cpu.flag_cond(FLAG_C, a < b);
-        Ok(())
+        cpu.set_sub_borrows(a, b, 0);
+        Ok(true)
     }
 }
 
@@ -67,7 +66,7 @@ mod tests {
             if r != REG_A {
                 assert_eq!(cpu.flag, 0b0100_0000);
             } else {
-                assert_eq!(cpu.flag, 0b1110_0000);
+                assert_eq!(cpu.flag, 0b1100_0000);
             }
         }
     }
@@ -91,10 +90,10 @@ mod tests {
     #[test]
     fn test_cp_regpair_addr_with_a() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0x11);
+        cpu.store_mem(0xff82, 0x11);
         cpu.reg[REG_A] = 0x9a;
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0xbe, None);
         assert_eq!(cpu.flag, 0b0100_0000);
     }
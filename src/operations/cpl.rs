@@ -7,11 +7,11 @@ use operations::Execute;
 pub struct ComplementA;
 
 impl Execute for ComplementA {
-    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         cpu.reg[REG_A] = !cpu.reg[REG_A];
         cpu.set_flag(FLAG_N);
         cpu.set_flag(FLAG_H);
-        Ok(())
+        Ok(true)
     }
 }
 
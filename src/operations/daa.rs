@@ -7,31 +7,34 @@ use operations::Execute;
 pub struct DecimalAdjustA;
 
 impl Execute for DecimalAdjustA {
-    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
-        let lo = cpu.reg[REG_A] & 0xf;
-        let hi = cpu.reg[REG_A] >> 4;
+    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
+        let mut a = cpu.reg[REG_A];
 
-        // if the least significant four bits of A contain a non-BCD digit (i. e.
-        // it is greater than 9) or the H flag is set: $06 is added to the register.
-        if lo > 9 || cpu.flag_is_set(FLAG_H) {
-            cpu.reg[REG_A] = cpu.reg[REG_A].wrapping_add(0x6);
-        }
-
-
-        // If the more significant digit also happens to be greater than 9 or the
-        // C flag is set: $60 is added
-        if (lo > 9 && hi > 9) || cpu.flag_is_set(FLAG_C) {
-            cpu.reg[REG_A] = cpu.reg[REG_A].wrapping_add(0x60);
-            cpu.set_flag(FLAG_C);
+        // Corrects the result of the last addition or subtraction of two BCD
+        // numbers, using the carries it produced. After an addition a digit
+        // above 9 also needs the correction.
+        if !cpu.flag_is_set(FLAG_N) {
+            if cpu.flag_is_set(FLAG_C) || a > 0x99 {
+                a = a.wrapping_add(0x60);
+                cpu.set_flag(FLAG_C);
+            }
+            if cpu.flag_is_set(FLAG_H) || a & 0xf > 9 {
+                a = a.wrapping_add(0x6);
+            }
         } else {
-            cpu.clear_flag(FLAG_C);
+            if cpu.flag_is_set(FLAG_C) {
+                a = a.wrapping_sub(0x60);
+            }
+            if cpu.flag_is_set(FLAG_H) {
+                a = a.wrapping_sub(0x6);
+            }
         }
 
-        let res = cpu.reg[REG_A];
-        cpu.flag_cond(FLAG_Z, res == 0);
+        cpu.reg[REG_A] = a;
+        cpu.flag_cond(FLAG_Z, a == 0);
         cpu.clear_flag(FLAG_H);
 
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -51,8 +54,8 @@ mod tests {
         let mut cpu = test_cpu();
         cpu.reg[REG_A] = 0b1010_0111;
         execute_instruction(&mut cpu, 0x27, None);
-        assert_eq!(cpu.reg[REG_A], 0b1010_0111);
-        assert_eq!(cpu.flag, 0b0000_0000);
+        assert_eq!(cpu.reg[REG_A], 0b0000_0111);
+        assert_eq!(cpu.flag, FLAG_C);
     }
 
     #[test]
@@ -72,4 +75,14 @@ mod tests {
         assert_eq!(cpu.reg[REG_A], 0b0001_0000);
     }
 
+    #[test]
+    fn test_daa_subtract() {
+        // 0x42 - 0x15
+        let mut cpu = test_cpu();
+        cpu.reg[REG_A] = 0x2d;
+        cpu.flag = FLAG_N | FLAG_H;
+        execute_instruction(&mut cpu, 0x27, None);
+        assert_eq!(cpu.reg[REG_A], 0x27);
+        assert_eq!(cpu.flag, FLAG_N);
+    }
 }
@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct Decrease;
 
 impl Execute for Decrease {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let dst = instruction.get_operand(0)?;
 
         match *dst {
@@ -16,40 +16,33 @@ impl Execute for Decrease {
                 let val = cpu.reg[r];
                 let res = val.wrapping_sub(1);
                 cpu.reg[r] = res;
-                cpu.set_half_carry(res as usize, 1);
+                cpu.flag_cond(FLAG_H, val & 0xf == 0);
                 cpu.flag_cond(FLAG_Z, res == 0);
+                cpu.set_flag(FLAG_N);
             },
+            // The 16 bit decrements leave the flags alone
             Operand::RegisterPair(h, l) => {
                 let val = cpu.read_reg_short(h, l);
-                let res = val.wrapping_sub(1);
-                cpu.store_reg_short(h, l, res);
-
-                // Make sure we calculate carry on high byte
-                cpu.set_half_carry((val >> 8) as usize, 1);
-                cpu.flag_cond(FLAG_Z, res == 0);
+                cpu.store_reg_short(h, l, val.wrapping_sub(1));
             },
             Operand::SP => {
-                let val = cpu.sp;
-                let res = val.wrapping_sub(1);
-                cpu.sp = res;
-                cpu.set_half_carry((val >> 8) as usize, 1);
-                cpu.flag_cond(FLAG_Z, res == 0);
+                cpu.sp = cpu.sp.wrapping_sub(1);
             },
             Operand::RegisterPairAddr(h, l) => {
                 let addr = cpu.read_reg_addr(h, l);
                 let val = cpu.load_mem(addr);
                 let res = val.wrapping_sub(1);
                 cpu.store_mem(addr, res);
-                cpu.set_half_carry(val as usize, 1);
+                cpu.flag_cond(FLAG_H, val & 0xf == 0);
                 cpu.flag_cond(FLAG_Z, res == 0);
+                cpu.set_flag(FLAG_N);
             },
             _ => {
                 println!("UNEXPECTED OPERANDS IN DEC");
             }
         };
 
-        cpu.clear_flag(FLAG_N);
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -90,7 +83,7 @@ mod tests {
         cpu.reg[REG_A] = 0x00;
         execute_instruction(&mut cpu, 0x3d, None);
         assert_eq!(cpu.reg[REG_A], 0xff);
-        assert_eq!(cpu.flag, 0b0010_0000);
+        assert_eq!(cpu.flag, 0b0110_0000);
     }
 
     #[test]
@@ -99,17 +92,17 @@ mod tests {
         cpu.reg[REG_A] = 0x10;
         execute_instruction(&mut cpu, 0x3d, None);
         assert_eq!(cpu.reg[REG_A], 0x0f);
-        assert_eq!(cpu.flag, 0b0010_0000);
+        assert_eq!(cpu.flag, 0b0110_0000);
     }
 
     #[test]
     fn test_dec_regpair_addr() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0x11);
+        cpu.store_mem(0xff82, 0x11);
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0x35, None);
-        assert_eq!(cpu.load_mem(0xff22), 0x10);
+        assert_eq!(cpu.load_mem(0xff82), 0x10);
     }
 
     #[test]
@@ -6,9 +6,9 @@ use operations::Execute;
 pub struct DisableInterrupts;
 
 impl Execute for DisableInterrupts {
-    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         cpu.disable_interrupts();
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -25,8 +25,8 @@ mod tests {
     #[test]
     fn test_disable_interrupts() {
         let mut cpu = test_cpu();
+        cpu.enable_interrupts();
         execute_instruction(&mut cpu, 0xf3, None);
-        let mem = cpu.mem.borrow();
-        assert_eq!(mem.interrupts, false)
+        assert!(!cpu.ime)
     }
 }
@@ -6,9 +6,9 @@ use operations::Execute;
 pub struct EnableInterrupts;
 
 impl Execute for EnableInterrupts {
-    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
-        cpu.enable_interrupts();
-        Ok(())
+    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
+        cpu.enable_interrupts_delayed();
+        Ok(true)
     }
 }
 
@@ -27,7 +27,10 @@ mod tests {
         let mut cpu = test_cpu();
         cpu.disable_interrupts();
         execute_instruction(&mut cpu, 0xfb, None);
-        let mem = cpu.mem.borrow();
-        assert_eq!(mem.interrupts, true);
+        assert!(!cpu.ime);
+
+        // Takes effect after the next instruction
+        execute_instruction(&mut cpu, 0x00, None);
+        assert!(cpu.ime);
     }
 }
@@ -1,4 +1,4 @@
-use cpu::{CPU, CPUState};
+use cpu::CPU;
 use instructions::Instruction;
 use errors::*;
 use operations::Execute;
@@ -6,9 +6,9 @@ use operations::Execute;
 pub struct Halt;
 
 impl Execute for Halt {
-    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
-        cpu.set_state(CPUState::Halted);
-        Ok(())
+    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
+        cpu.halt();
+        Ok(true)
     }
 }
 
@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct Increase;
 
 impl Execute for Increase {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let dst = instruction.get_operand(0)?;
 
         match *dst {
@@ -18,22 +18,15 @@ impl Execute for Increase {
                 cpu.reg[r] = res;
                 cpu.set_half_carry(val as usize, 1);
                 cpu.flag_cond(FLAG_Z, res == 0);
+                cpu.clear_flag(FLAG_N);
             },
+            // The 16 bit increments leave the flags alone
             Operand::RegisterPair(h, l) => {
                 let val = cpu.read_reg_short(h, l);
-                let res = val.wrapping_add(1);
-                cpu.store_reg_short(h, l, res);
-
-                // Make sure we calculate carry on high byte
-                cpu.set_half_carry((val >> 8) as usize, 1);
-                cpu.flag_cond(FLAG_Z, res == 0);
+                cpu.store_reg_short(h, l, val.wrapping_add(1));
             },
             Operand::SP => {
-                let val = cpu.sp;
-                let res = val.wrapping_add(1);
-                cpu.sp = res;
-                cpu.set_half_carry((val >> 8) as usize, 1);
-                cpu.flag_cond(FLAG_Z, res == 0);
+                cpu.sp = cpu.sp.wrapping_add(1);
             },
             Operand::RegisterPairAddr(h, l) => {
                 let addr = cpu.read_reg_addr(h, l);
@@ -42,14 +35,14 @@ impl Execute for Increase {
                 cpu.store_mem(addr, res);
                 cpu.set_half_carry(val as usize, 1);
                 cpu.flag_cond(FLAG_Z, res == 0);
+                cpu.clear_flag(FLAG_N);
             },
             _ => {
                 println!("UNEXPECTED OPERANDS IN INC");
             }
         };
 
-        cpu.clear_flag(FLAG_N);
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -105,11 +98,11 @@ mod tests {
     #[test]
     fn test_inc_regpair_addr() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0x11);
+        cpu.store_mem(0xff82, 0x11);
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0x34, None);
-        assert_eq!(cpu.load_mem(0xff22), 0x12);
+        assert_eq!(cpu.load_mem(0xff82), 0x12);
     }
 
     #[test]
@@ -137,4 +130,15 @@ mod tests {
         execute_instruction(&mut cpu, 0x33, None);
         assert_eq!(cpu.sp, 0xaabc);
     }
+
+    #[test]
+    fn test_inc_regpair_keeps_flags() {
+        let mut cpu = test_cpu();
+        cpu.reg[REG_B] = 0xff;
+        cpu.reg[REG_C] = 0xff;
+        cpu.flag = 0b0101_0000;
+        execute_instruction(&mut cpu, 0x03, None);
+        assert_eq!(cpu.read_reg_short(REG_B, REG_C), 0);
+        assert_eq!(cpu.flag, 0b0101_0000);
+    }
 }
@@ -8,46 +8,32 @@ use operations::Execute;
 pub struct Jump;
 
 impl Execute for Jump {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let op1 = instruction.get_operand(0)?;
         let op2 = instruction.get_operand(1)?;
 
-        match (op1, op2) {
-            (&Operand::Address(SHORT), &Operand::None) => {
-                cpu.pc = instruction.get_immediate_u16()?;
-            },
-            (&Operand::RegisterPairAddr(h, l), &Operand::None) => {
-                let a = cpu.read_reg_addr(h, l);
-                cpu.pc = a as u16;
-            },
-            (&Operand::Zero, &Operand::Address(SHORT))  => {
-                if cpu.flag_is_set(FLAG_Z) {
-                    cpu.pc = instruction.get_immediate_u16()?;
-                }
-            },
-            (&Operand::NonZero, &Operand::Address(SHORT)) => {
-                if !cpu.flag_is_set(FLAG_Z) {
-                    cpu.pc = instruction.get_immediate_u16()?;
-                }
-            },
-            (&Operand::Carry, &Operand::Address(SHORT)) => {
-                if cpu.flag_is_set(FLAG_C) {
-                    cpu.pc = instruction.get_immediate_u16()?;
-                }
-            },
-            (&Operand::NonCarry, &Operand::Address(SHORT)) => {
-                if !cpu.flag_is_set(FLAG_C) {
-                    cpu.pc = instruction.get_immediate_u16()?;
-                }
-            },
+        let should_jump = match (op1, op2) {
+            (&Operand::Address(SHORT), &Operand::None) |
+            (&Operand::RegisterPairAddr(_, _), &Operand::None) => true,
+            (&Operand::Zero, &Operand::Address(SHORT)) => cpu.flag_is_set(FLAG_Z),
+            (&Operand::NonZero, &Operand::Address(SHORT)) => !cpu.flag_is_set(FLAG_Z),
+            (&Operand::Carry, &Operand::Address(SHORT)) => cpu.flag_is_set(FLAG_C),
+            (&Operand::NonCarry, &Operand::Address(SHORT)) => !cpu.flag_is_set(FLAG_C),
             _ => {
                 println!("UNEXPECTED OPERAND {} {}", op1, op2);
+                false
             }
         };
 
-        cpu.pc = cpu.pc.wrapping_sub(instruction.definition.length as u16);
+        if should_jump {
+            cpu.pc = match *op1 {
+                Operand::RegisterPairAddr(h, l) => cpu.read_reg_addr(h, l) as u16,
+                _ => instruction.get_immediate_u16()?,
+            };
+            cpu.pc = cpu.pc.wrapping_sub(instruction.definition.length as u16);
+        }
 
-        Ok(())
+        Ok(should_jump)
     }
 }
 
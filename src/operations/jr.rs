@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct JumpRelative;
 
 impl Execute for JumpRelative {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let op1 = instruction.get_operand(0)?;
         let op2 = instruction.get_operand(1)?;
 
@@ -31,7 +31,7 @@ impl Execute for JumpRelative {
 
         // TODO: Accommodate for next inc of program counter?
 
-        Ok(())
+        Ok(should_jump)
     }
 }
 
@@ -79,4 +79,17 @@ mod tests {
             assert_eq!(cpu.pc, 0xff32 + 2);
         }
     }
+
+    #[test]
+    fn test_jr_cycles() {
+        let mut cpu = test_cpu();
+        cpu.flag_cond(FLAG_Z, false);
+        execute_instruction(&mut cpu, 0x20, Some(0x10));
+        assert_eq!(cpu.cycles, 12);
+
+        let mut cpu = test_cpu();
+        cpu.flag_cond(FLAG_Z, true);
+        execute_instruction(&mut cpu, 0x20, Some(0x10));
+        assert_eq!(cpu.cycles, 8);
+    }
 }
@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct Load;
 
 impl Execute for Load {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let dst = instruction.get_operand(0)?;
         let src = instruction.get_operand(1)?;
 
@@ -36,18 +36,17 @@ impl Execute for Load {
                 cpu.store_reg_short(h, l, instruction.get_immediate_u16()?);
             },
             (&Operand::RegisterPair(h, l), &Operand::SPOffset(BYTE)) => {
-                let offset = instruction.get_immediate_i16()?;
+                let offset = instruction.get_immediate_i8()?;
                 let sp = cpu.sp;
-                let val = sp as i16 + offset;
+                let val = sp.wrapping_add(offset as u16);
 
-                cpu.store_reg_short(h, l, val as u16);
+                cpu.store_reg_short(h, l, val);
 
                 cpu.clear_flag(FLAG_Z);
                 cpu.clear_flag(FLAG_N);
 
-                // TODO: Double check these. _Really_ Unsafe arithmetic.
-                cpu.set_half_carry(sp as usize, offset as usize);
-                cpu.set_carry(sp as usize, offset as usize);
+                // The carries come from adding the offset to the low byte
+                cpu.set_add_carries(sp as u8, offset as u8, 0);
             },
             (&Operand::RegisterAddr(r1), &Operand::Register(r2)) => {
                 let offset = cpu.reg[r1] as usize;
@@ -85,7 +84,7 @@ impl Execute for Load {
                 println!("UNEXPECTED OPERANDS {} {}", dst, src);
             },
         };
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -193,9 +192,9 @@ mod tests {
 
         for &(c, r) in reg_codes.iter() {
             let mut cpu = test_cpu();
-            cpu.store_mem(0xff22, 0xab);
+            cpu.store_mem(0xff82, 0xab);
             cpu.reg[REG_H] = 0xff;
-            cpu.reg[REG_L] = 0x22;
+            cpu.reg[REG_L] = 0x82;
             execute_instruction(&mut cpu, c, None);
             assert_eq!(cpu.reg[r], 0xab);
         }
@@ -216,14 +215,14 @@ mod tests {
             let mut cpu = test_cpu();
             let ex = match r {
                 REG_H => 0xff,
-                REG_L => 0x22,
+                REG_L => 0x82,
                 _ => 0xab,
             };
             cpu.reg[r] = ex;
             cpu.reg[REG_H] = 0xff;
-            cpu.reg[REG_L] = 0x22;
+            cpu.reg[REG_L] = 0x82;
             execute_instruction(&mut cpu, c, None);
-            assert_eq!(cpu.load_mem(0xff22), ex);
+            assert_eq!(cpu.load_mem(0xff82), ex);
         }
     }
 
@@ -231,9 +230,9 @@ mod tests {
     fn test_ld_byte_to_regpair_addr() {
         let mut cpu = test_cpu();
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0x36, Some(0xab));
-        assert_eq!(cpu.load_mem(0xff22), 0xab);
+        assert_eq!(cpu.load_mem(0xff82), 0xab);
     }
 
     #[test]
@@ -245,9 +244,9 @@ mod tests {
 
         for &(c, h, l) in pairs.iter() {
             let mut cpu = test_cpu();
-            cpu.store_mem(0xff22, 0xab);
+            cpu.store_mem(0xff82, 0xab);
             cpu.reg[h] = 0xff;
-            cpu.reg[l] = 0x22;
+            cpu.reg[l] = 0x82;
             execute_instruction(&mut cpu, c, None);
             assert_eq!(cpu.reg[REG_A], 0xab);
         }
@@ -256,8 +255,8 @@ mod tests {
     #[test]
     fn test_ld_immediate_addr_to_a() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0xab);
-        execute_instruction(&mut cpu, 0xfa, Some(0xff22));
+        cpu.store_mem(0xff82, 0xab);
+        execute_instruction(&mut cpu, 0xfa, Some(0xff82));
         assert_eq!(cpu.reg[REG_A], 0xab);
     }
 
@@ -272,10 +271,10 @@ mod tests {
         for &(c, h, l) in pairs.iter() {
             let mut cpu = test_cpu();
             cpu.reg[h] = 0xff;
-            cpu.reg[l] = 0x22;
+            cpu.reg[l] = 0x82;
             cpu.reg[REG_A] = 0xab;
             execute_instruction(&mut cpu, c, None);
-            assert_eq!(cpu.load_mem(0xff22), 0xab);
+            assert_eq!(cpu.load_mem(0xff82), 0xab);
         }
     }
 
@@ -283,15 +282,15 @@ mod tests {
     fn test_ld_a_to_immediate_addr() {
         let mut cpu = test_cpu();
         cpu.reg[REG_A] = 0xab;
-        execute_instruction(&mut cpu, 0xea, Some(0xff22));
-        assert_eq!(cpu.load_mem(0xff22), 0xab);
+        execute_instruction(&mut cpu, 0xea, Some(0xff82));
+        assert_eq!(cpu.load_mem(0xff82), 0xab);
     }
 
     #[test]
     fn test_ld_c_addr_offset_to_a() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0xab);
-        cpu.reg[REG_C] = 0x22;
+        cpu.store_mem(0xff82, 0xab);
+        cpu.reg[REG_C] = 0x82;
         execute_instruction(&mut cpu, 0xf2, None);
         assert_eq!(cpu.reg[REG_A], 0xab);
     }
@@ -300,9 +299,9 @@ mod tests {
     fn test_ld_a_to_c_addr_offset() {
         let mut cpu = test_cpu();
         cpu.reg[REG_A] = 0xab;
-        cpu.reg[REG_C] = 0x22;
+        cpu.reg[REG_C] = 0x82;
         execute_instruction(&mut cpu, 0xe2, None);
-        assert_eq!(cpu.load_mem(0xff22), 0xab);
+        assert_eq!(cpu.load_mem(0xff82), 0xab);
     }
 
     #[test]
@@ -350,8 +349,17 @@ mod tests {
     fn test_ld_sp_to_addr() {
         let mut cpu = test_cpu();
         cpu.sp = 0xaabb;
-        execute_instruction(&mut cpu, 0x08, Some(0xff22));
-        assert_eq!(cpu.load_mem(0xff22), 0xbb);
-        assert_eq!(cpu.load_mem(0xff23), 0xaa);
+        execute_instruction(&mut cpu, 0x08, Some(0xff92));
+        assert_eq!(cpu.load_mem(0xff92), 0xbb);
+        assert_eq!(cpu.load_mem(0xff93), 0xaa);
+    }
+
+    #[test]
+    fn test_ld_negative_sp_offset_to_hl() {
+        let mut cpu = test_cpu();
+        cpu.sp = 0x1002;
+        execute_instruction(&mut cpu, 0xf8, Some(0xfe));
+        assert_eq!(cpu.read_reg_short(REG_H, REG_L), 0x1000);
+        assert_eq!(cpu.flag, 0b0011_0000);
     }
 }
@@ -7,7 +7,7 @@ use operations::Execute;
 pub struct LoadDecrease;
 
 impl Execute for LoadDecrease {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let dst = instruction.get_operand(0)?;
         let src = instruction.get_operand(1)?;
 
@@ -28,7 +28,7 @@ impl Execute for LoadDecrease {
                 println!("UNEXPECTED OPERANDS {} {}", src, dst);
             }
         };
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -46,13 +46,13 @@ mod tests {
     #[test]
     fn test_ldd_hl_addr_to_a() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0xab);
+        cpu.store_mem(0xff82, 0xab);
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0x3a, None);
         assert_eq!(cpu.reg[REG_A], 0xab);
         assert_eq!(cpu.reg[REG_H], 0xff);
-        assert_eq!(cpu.reg[REG_L], 0x21);
+        assert_eq!(cpu.reg[REG_L], 0x81);
     }
 
     #[test]
@@ -60,10 +60,10 @@ mod tests {
         let mut cpu = test_cpu();
         cpu.reg[REG_A] = 0xab;
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0x32, None);
-        assert_eq!(cpu.load_mem(0xff22), 0xab);
+        assert_eq!(cpu.load_mem(0xff82), 0xab);
         assert_eq!(cpu.reg[REG_H], 0xff);
-        assert_eq!(cpu.reg[REG_L], 0x21);
+        assert_eq!(cpu.reg[REG_L], 0x81);
     }
 }
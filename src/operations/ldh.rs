@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct LoadOffset;
 
 impl Execute for LoadOffset {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let dst = instruction.get_operand(0)?;
         let src = instruction.get_operand(1)?;
 
@@ -27,7 +27,7 @@ impl Execute for LoadOffset {
                 println!("UNEXPECTED OPERANDS {} {}", src, dst);
             }
         };
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -46,15 +46,15 @@ mod tests {
     fn test_ldh_a_to_immediate_offset_addr() {
         let mut cpu = test_cpu();
         cpu.reg[REG_A] = 0xab;
-        execute_instruction(&mut cpu, 0xe0, Some(0x22));
-        assert_eq!(cpu.load_mem(0xff22), 0xab);
+        execute_instruction(&mut cpu, 0xe0, Some(0x82));
+        assert_eq!(cpu.load_mem(0xff82), 0xab);
     }
 
     #[test]
     fn test_ldhimmediate_offset_addr_to_a() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0xab);
-        execute_instruction(&mut cpu, 0xf0, Some(0x22));
+        cpu.store_mem(0xff82, 0xab);
+        execute_instruction(&mut cpu, 0xf0, Some(0x82));
         assert_eq!(cpu.reg[REG_A], 0xab);
     }
 }
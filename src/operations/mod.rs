@@ -97,5 +97,8 @@ use cpu::CPU;
 use errors::*;
 
 pub trait Execute {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()>;
+    // Returns false only for conditional branches that were not taken,
+    // which cost the shorter of the two cycle counts of the definition.
+    // Every other instruction returns true.
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool>;
 }
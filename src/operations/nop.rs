@@ -6,8 +6,8 @@ use operations::Execute;
 pub struct Nop;
 
 impl Execute for Nop {
-    fn execute(_instruction: &Instruction, _cpu: &mut CPU) -> Result<()> {
-        Ok(())
+    fn execute(_instruction: &Instruction, _cpu: &mut CPU) -> Result<bool> {
+        Ok(true)
     }
 }
 
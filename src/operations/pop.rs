@@ -7,20 +7,20 @@ use operations::Execute;
 pub struct Pop;
 
 impl Execute for Pop {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let src = instruction.get_operand(0)?;
 
         match *src {
             Operand::RegisterPair(h, l) => {
-                cpu.reg[h] = cpu.stack_pop();
-                cpu.reg[l] = cpu.stack_pop();
+                let value = cpu.stack_pop_u16();
+                cpu.store_reg_short(h, l, value);
             },
             _ => {
                 println!("UNEXPECTED OPERAND {}", src);
             }
         };
 
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -46,13 +46,25 @@ mod tests {
 
         for &(c, h, l) in pairs.iter() {
             let mut cpu = test_cpu();
-            cpu.store_mem(0xff22, 0xaa);
-            cpu.store_mem(0xff23, 0xbb);
-            cpu.sp = 0xff22;
+            cpu.store_mem(0xff92, 0xb0);
+            cpu.store_mem(0xff93, 0xaa);
+            cpu.sp = 0xff92;
             execute_instruction(&mut cpu, c, None);
             assert_eq!(cpu.reg[h], 0xaa);
-            assert_eq!(cpu.reg[l], 0xbb);
-            assert_eq!(cpu.sp, 0xff24);
+            assert_eq!(cpu.reg[l], 0xb0);
+            assert_eq!(cpu.sp, 0xff94);
         }
     }
+
+    #[test]
+    fn test_pop_af() {
+        let mut cpu = test_cpu();
+        cpu.store_mem(0xff92, 0xff);
+        cpu.store_mem(0xff93, 0x12);
+        cpu.sp = 0xff92;
+        execute_instruction(&mut cpu, 0xf1, None);
+        assert_eq!(cpu.reg[REG_A], 0x12);
+        // The lower four bits of F do not exist
+        assert_eq!(cpu.flag, 0xf0);
+    }
 }
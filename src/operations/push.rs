@@ -7,22 +7,21 @@ use operations::Execute;
 pub struct Push;
 
 impl Execute for Push {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let src = instruction.get_operand(0)?;
 
         match *src {
             Operand::RegisterPair(h, l) => {
-                let lo = cpu.reg[l];
-                let hi = cpu.reg[h];
-                cpu.stack_push(lo);
-                cpu.stack_push(hi);
+                let value = cpu.read_reg_short(h, l);
+                cpu.internal_cycle();
+                cpu.stack_push_u16(value);
             },
             _ => {
                 println!("UNEXPECTED OPERAND {}", src);
             }
         };
 
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -48,13 +47,23 @@ mod tests {
 
         for &(c, h, l) in pairs.iter() {
             let mut cpu = test_cpu();
-            cpu.sp = 0xff22;
-            cpu.reg[h] = 0xaa;
-            cpu.reg[l] = 0xbb;
+            cpu.sp = 0xff92;
+            cpu.store_reg_short(h, l, 0xaab0);
             execute_instruction(&mut cpu, c, None);
-            assert_eq!(cpu.load_mem(0xff21), 0xbb);
-            assert_eq!(cpu.load_mem(0xff20), 0xaa);
-            assert_eq!(cpu.sp, 0xff20);
+            assert_eq!(cpu.load_mem(0xff91), 0xaa);
+            assert_eq!(cpu.load_mem(0xff90), 0xb0);
+            assert_eq!(cpu.sp, 0xff90);
         }
     }
+
+    #[test]
+    fn test_push_af() {
+        let mut cpu = test_cpu();
+        cpu.sp = 0xff92;
+        cpu.reg[REG_A] = 0x12;
+        cpu.flag = FLAG_Z | FLAG_C;
+        execute_instruction(&mut cpu, 0xf5, None);
+        assert_eq!(cpu.load_mem(0xff91), 0x12);
+        assert_eq!(cpu.load_mem(0xff90), 0x90);
+    }
 }
@@ -7,7 +7,7 @@ use operations::Execute;
 pub struct Reset;
 
 impl Execute for Reset {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
 
         let bit = instruction.get_operand(0)?;
         let src = instruction.get_operand(1)?;
@@ -26,7 +26,7 @@ impl Execute for Reset {
                 println!("UNEXPECTED OPERAND {}", src);
             },
         };
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -67,11 +67,11 @@ mod tests {
     fn test_res_regpair_addr() {
         for bit in 0..8 {
             let mut cpu = test_cpu();
-            cpu.store_mem(0xff22, 1u8 << bit);
+            cpu.store_mem(0xff82, 1u8 << bit);
             cpu.reg[REG_H] = 0xff;
-            cpu.reg[REG_L] = 0x22;
+            cpu.reg[REG_L] = 0x82;
             execute_instruction(&mut cpu, 0xcb86 + 8 * bit, None);
-            assert_eq!(cpu.load_mem(0xff22), 0);
+            assert_eq!(cpu.load_mem(0xff82), 0);
         }
     }
 }
@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct Return;
 
 impl Execute for Return {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let op = instruction.get_operand(0)?;
 
         let should_return = match *op {
@@ -23,16 +23,20 @@ impl Execute for Return {
             }
         };
 
-        if should_return {
-            let hi = cpu.stack_pop() as u16;
-            let lo = cpu.stack_pop() as u16;
-            cpu.pc = (hi << 8) | lo;
+        if *op != Operand::None {
+            // Evaluating the condition takes a cycle of its own
+            cpu.internal_cycle();
         }
 
-        // Accommodate for next inc of program counter
-        cpu.pc = cpu.pc.wrapping_sub(instruction.definition.length as u16);
+        if should_return {
+            cpu.pc = cpu.stack_pop_u16();
+            cpu.internal_cycle();
 
-        Ok(())
+            // Accommodate for next inc of program counter
+            cpu.pc = cpu.pc.wrapping_sub(instruction.definition.length as u16);
+        }
+
+        Ok(should_return)
     }
 }
 
@@ -51,8 +55,7 @@ mod tests {
     fn test_ret() {
         let mut cpu = test_cpu();
         cpu.sp = 0x1122;
-        cpu.stack_push(0x22);
-        cpu.stack_push(0xff);
+        cpu.stack_push_u16(0xff22);
         execute_instruction(&mut cpu, 0xc9, None);
         assert_eq!(cpu.pc, 0xff22);
         assert_eq!(cpu.sp, 0x1122);
@@ -70,12 +73,23 @@ mod tests {
         for &(c, f, s) in flag_set_codes.iter() {
             let mut cpu = test_cpu();
             cpu.sp = 0x1122;
-            cpu.stack_push(0x22);
-            cpu.stack_push(0xff);
+            cpu.stack_push_u16(0xff22);
             cpu.flag_cond(f, s);
             execute_instruction(&mut cpu, c, None);
             assert_eq!(cpu.pc, 0xff22);
             assert_eq!(cpu.sp, 0x1122);
         }
     }
+
+    #[test]
+    fn test_ret_not_taken() {
+        let mut cpu = test_cpu();
+        cpu.pc = 0x1234;
+        cpu.sp = 0x1122;
+        cpu.flag_cond(FLAG_Z, true);
+        execute_instruction(&mut cpu, 0xc0, None);
+        assert_eq!(cpu.pc, 0x1235);
+        assert_eq!(cpu.sp, 0x1122);
+        assert_eq!(cpu.cycles, 8);
+    }
 }
@@ -6,16 +6,15 @@ use operations::Execute;
 pub struct ReturnEnableInterrupts;
 
 impl Execute for ReturnEnableInterrupts {
-    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
-        let hi = cpu.stack_pop() as u16;
-        let lo = cpu.stack_pop() as u16;
-        cpu.pc = (hi << 8) | lo;
+    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
+        cpu.pc = cpu.stack_pop_u16();
+        cpu.internal_cycle();
         cpu.enable_interrupts();
 
         // Accommodate for next inc of program counter
         cpu.pc = cpu.pc.wrapping_sub(_instruction.definition.length as u16);
 
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -34,12 +33,10 @@ mod tests {
         let mut cpu = test_cpu();
         cpu.sp = 0x1122;
         cpu.disable_interrupts();
-        cpu.stack_push(0x22);
-        cpu.stack_push(0xff);
+        cpu.stack_push_u16(0xff22);
         execute_instruction(&mut cpu, 0xd9, None);
         assert_eq!(cpu.pc, 0xff22);
         assert_eq!(cpu.sp, 0x1122);
-        let mem = cpu.mem.borrow();
-        assert_eq!(mem.interrupts, true);
+        assert!(cpu.ime);
     }
 }
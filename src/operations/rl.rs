@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct RotateLeft;
 
 impl Execute for RotateLeft {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let src = instruction.get_operand(0)?;
 
         match *src {
@@ -32,7 +32,7 @@ impl Execute for RotateLeft {
                 let addr = cpu.read_reg_addr(h, l);
                 let val = cpu.load_mem(addr);
                 let msb = val >> 7;
-                let mut res = (val << 1) | msb;
+                let mut res = val << 1;
 
                 if cpu.flag_is_set(FLAG_C) {
                     res |= 1;
@@ -51,7 +51,7 @@ impl Execute for RotateLeft {
             },
         };
 
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -113,11 +113,22 @@ mod tests {
     #[test]
     fn test_rl_regpair_addr_no_carry() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0b0111_1111);
+        cpu.store_mem(0xff82, 0b0111_1111);
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0xcb06, None);
-        assert_eq!(cpu.load_mem(0xff22), 0b1111_1110);
+        assert_eq!(cpu.load_mem(0xff82), 0b1111_1110);
         assert_eq!(cpu.flag, 0b0000_0000);
     }
+
+    #[test]
+    fn test_rl_regpair_addr_carry() {
+        let mut cpu = test_cpu();
+        cpu.store_mem(0xff82, 0b1000_0000);
+        cpu.reg[REG_H] = 0xff;
+        cpu.reg[REG_L] = 0x82;
+        execute_instruction(&mut cpu, 0xcb16, None);
+        assert_eq!(cpu.load_mem(0xff82), 0b0000_0000);
+        assert_eq!(cpu.flag, 0b1001_0000);
+    }
 }
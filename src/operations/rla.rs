@@ -7,7 +7,7 @@ use operations::Execute;
 pub struct RotateALeft;
 
 impl Execute for RotateALeft {
-    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let val = cpu.reg[REG_A];
         let msb = val >> 7;
         let mut res = val << 1;
@@ -17,12 +17,12 @@ impl Execute for RotateALeft {
         }
         cpu.reg[REG_A] = res;
 
-        cpu.flag_cond(FLAG_Z, res == 0);
+        cpu.clear_flag(FLAG_Z);
         cpu.clear_flag(FLAG_N);
         cpu.clear_flag(FLAG_H);
         cpu.flag_cond(FLAG_C, msb == 1);
 
-        Ok(())
+        Ok(true)
     }
 }
 
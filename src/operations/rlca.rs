@@ -7,18 +7,18 @@ use operations::Execute;
 pub struct RotateALeftCarry;
 
 impl Execute for RotateALeftCarry {
-    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let val = cpu.reg[REG_A];
         let msb = val >> 7;
         let res = (val << 1) | msb;
         cpu.reg[REG_A] = res;
 
-        cpu.flag_cond(FLAG_Z, res == 0);
+        cpu.clear_flag(FLAG_Z);
         cpu.clear_flag(FLAG_N);
         cpu.clear_flag(FLAG_H);
         cpu.flag_cond(FLAG_C, msb == 1);
 
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -50,4 +50,14 @@ mod tests {
         assert_eq!(cpu.reg[REG_A], 0b1111_1111);
         assert_eq!(cpu.flag, 0b0001_0000);
     }
+
+    #[test]
+    fn test_rlca_zero() {
+        let mut cpu = test_cpu();
+        cpu.reg[REG_A] = 0;
+        execute_instruction(&mut cpu, 0x07, None);
+        assert_eq!(cpu.reg[REG_A], 0);
+        // Unlike RLC A, Z is always cleared
+        assert_eq!(cpu.flag, 0b0000_0000);
+    }
 }
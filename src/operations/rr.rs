@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct RotateRight;
 
 impl Execute for RotateRight {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let src = instruction.get_operand(0)?;
 
         match *src {
@@ -51,7 +51,7 @@ impl Execute for RotateRight {
             },
         };
 
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -113,11 +113,11 @@ mod tests {
     #[test]
     fn test_rr_regpair_addr_no_carry() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0b1111_1110);
+        cpu.store_mem(0xff82, 0b1111_1110);
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0xcb1e, None);
-        assert_eq!(cpu.load_mem(0xff22), 0b0111_1111);
+        assert_eq!(cpu.load_mem(0xff82), 0b0111_1111);
         assert_eq!(cpu.flag, 0b0000_0000);
     }
 }
@@ -7,7 +7,7 @@ use operations::Execute;
 pub struct RotateARight;
 
 impl Execute for RotateARight {
-    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let val = cpu.reg[REG_A];
         let lsb = val & 0x1;
         let mut res = val >> 1;
@@ -18,13 +18,12 @@ impl Execute for RotateARight {
 
         cpu.reg[REG_A] = res;
 
-        cpu.flag_cond(FLAG_Z, res == 0);
         cpu.clear_flag(FLAG_Z);
         cpu.clear_flag(FLAG_N);
         cpu.clear_flag(FLAG_H);
         cpu.flag_cond(FLAG_C, lsb == 1);
 
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -7,13 +7,13 @@ use operations::Execute;
 pub struct Restart;
 
 impl Execute for Restart {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let offset = instruction.get_operand(0)?;
 
         if let Operand::RSTOffset(o) = *offset {
-            let addr = cpu.pc;
-            cpu.stack_push((addr & 0xff) as u8);
-            cpu.stack_push((addr >> 8) as u8);
+            let addr = cpu.pc.wrapping_add(instruction.definition.length as u16);
+            cpu.internal_cycle();
+            cpu.stack_push_u16(addr);
             cpu.pc = o as u16;
         } else {
             println!("UNEXPECTED OPERAND {}", offset);
@@ -23,7 +23,7 @@ impl Execute for Restart {
         cpu.pc = cpu.pc.wrapping_sub(instruction.definition.length as u16);
 
 
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -56,8 +56,9 @@ mod tests {
             cpu.sp = 0x1122;
             execute_instruction(&mut cpu, c, None);
             assert_eq!(cpu.pc, o);
-            assert_eq!(cpu.load_mem(0x1121), 0x33);
-            assert_eq!(cpu.load_mem(0x1120), 0x22);
+            // Returns to the instruction after the RST
+            assert_eq!(cpu.load_mem(0x1121), 0x22);
+            assert_eq!(cpu.load_mem(0x1120), 0x34);
         }
     }
 }
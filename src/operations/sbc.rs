@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct SubtractCarry;
 
 impl Execute for SubtractCarry {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let src = instruction.get_operand(1)?;
 
         match *src {
@@ -16,53 +16,40 @@ impl Execute for SubtractCarry {
                 let a = cpu.reg[REG_A];
                 let b = cpu.reg[r];
 
-                // TODO: CHECK THIS
-                let mut val = a.wrapping_sub(b);
-
-                if cpu.flag_is_set(FLAG_C) {
-                    val = val.wrapping_sub(1);
-                }
+                let carry = cpu.flag_is_set(FLAG_C) as u8;
+                let val = a.wrapping_sub(b).wrapping_sub(carry);
 
                 cpu.reg[REG_A] = val;
 
                 cpu.flag_cond(FLAG_Z, val == 0);
                 cpu.set_flag(FLAG_N);
-                cpu.set_half_carry(a as usize, b as usize);
-                cpu.set_carry(a as usize, b as usize);
+                cpu.set_sub_borrows(a, b, carry);
             },
             Operand::RegisterPairAddr(h, l) => {
                 let addr = cpu.read_reg_addr(h, l);
                 let a = cpu.reg[REG_A];
                 let b = cpu.load_mem(addr);
 
-                let mut val = a.wrapping_sub(b);
-
-                if cpu.flag_is_set(FLAG_C) {
-                    val = val.wrapping_sub(1);
-                }
+                let carry = cpu.flag_is_set(FLAG_C) as u8;
+                let val = a.wrapping_sub(b).wrapping_sub(carry);
 
                 cpu.reg[REG_A] = val;
 
                 cpu.flag_cond(FLAG_Z, val == 0);
                 cpu.set_flag(FLAG_N);
-                cpu.set_half_carry(a as usize, b as usize);
-                cpu.set_carry(a as usize, b as usize);
+                cpu.set_sub_borrows(a, b, carry);
             },
             Operand::Immediate(BYTE) => {
                 let a = cpu.reg[REG_A];
                 let b = instruction.get_immediate_u8()?;
-                let mut val = a.wrapping_sub(b);
-
-                if cpu.flag_is_set(FLAG_C) {
-                    val = val.wrapping_sub(1);
-                }
+                let carry = cpu.flag_is_set(FLAG_C) as u8;
+                let val = a.wrapping_sub(b).wrapping_sub(carry);
 
                 cpu.reg[REG_A] = val;
 
                 cpu.flag_cond(FLAG_Z, val == 0);
                 cpu.set_flag(FLAG_N);
-                cpu.set_half_carry(a as usize, b as usize);
-                cpu.set_carry(a as usize, b as usize);
+                cpu.set_sub_borrows(a, b, carry);
 
             },
             _ => {
@@ -70,7 +57,7 @@ impl Execute for SubtractCarry {
                 println!("UNEXPECTED OPERANDS {}", src);
             },
         };
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -124,11 +111,11 @@ mod tests {
     fn test_sbc_reg_from_a_half_carry() {
         for carry in 0..2 {
             let mut cpu = test_cpu();
-            cpu.reg[REG_A] = 0x7f;
+            cpu.reg[REG_A] = 0x80;
             cpu.reg[REG_B] = 0x01;
             cpu.flag_cond(FLAG_C, carry == 1);
             execute_instruction(&mut cpu, 0x98, None);
-            assert_eq!(cpu.reg[REG_A], 0x7e - carry);
+            assert_eq!(cpu.reg[REG_A], 0x7f - carry);
             assert_eq!(cpu.flag, 0b0110_0000);
         }
     }
@@ -142,7 +129,7 @@ mod tests {
             cpu.flag_cond(FLAG_C, carry == 1);
             execute_instruction(&mut cpu, 0x98, None);
             assert_eq!(cpu.reg[REG_A], 0xff - carry);
-            assert_eq!(cpu.flag, 0b0101_0000);
+            assert_eq!(cpu.flag, 0b0111_0000);
         }
     }
 
@@ -172,10 +159,10 @@ mod tests {
     fn test_sbc_regpair_addr_from_a() {
         for carry in 0..2 {
             let mut cpu = test_cpu();
-            cpu.store_mem(0xff22, 0x11);
+            cpu.store_mem(0xff82, 0x11);
             cpu.reg[REG_A] = 0x9a;
             cpu.reg[REG_H] = 0xff;
-            cpu.reg[REG_L] = 0x22;
+            cpu.reg[REG_L] = 0x82;
             cpu.flag_cond(FLAG_C, carry == 1);
             execute_instruction(&mut cpu, 0x9e, None);
             assert_eq!(cpu.reg[REG_A], 0x89 - carry);
@@ -7,11 +7,11 @@ use operations::Execute;
 pub struct SetCarryFlag;
 
 impl Execute for SetCarryFlag {
-    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         cpu.clear_flag(FLAG_N);
         cpu.clear_flag(FLAG_H);
         cpu.set_flag(FLAG_C);
-        Ok(())
+        Ok(true)
     }
 }
 
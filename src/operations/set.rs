@@ -7,7 +7,7 @@ use operations::Execute;
 pub struct Set;
 
 impl Execute for Set {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
 
         let bit = instruction.get_operand(0)?;
         let src = instruction.get_operand(1)?;
@@ -26,7 +26,7 @@ impl Execute for Set {
                 println!("UNEXPECTED OPERAND {}", src);
             },
         };
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -61,9 +61,9 @@ mod tests {
         for bit in 0..8 {
             let mut cpu = test_cpu();
             cpu.reg[REG_H] = 0xff;
-            cpu.reg[REG_L] = 0x22;
+            cpu.reg[REG_L] = 0x82;
             execute_instruction(&mut cpu, 0xcbc6 + 8 * bit, None);
-            assert_eq!(cpu.load_mem(0xff22), 1 << bit);
+            assert_eq!(cpu.load_mem(0xff82), 1 << bit);
         }
     }
 }
@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct ShiftLeftArithmetic;
 
 impl Execute for ShiftLeftArithmetic {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let src = instruction.get_operand(0)?;
 
         match *src {
@@ -28,7 +28,7 @@ impl Execute for ShiftLeftArithmetic {
                 let addr = cpu.read_reg_addr(h, l);
                 let val = cpu.load_mem(addr);
                 let msb = val >> 7;
-                let mut res = val << 1;
+                let res = val << 1;
 
                 cpu.store_mem(addr, res);
 
@@ -43,7 +43,7 @@ impl Execute for ShiftLeftArithmetic {
             },
         };
 
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -105,11 +105,11 @@ mod tests {
     #[test]
     fn test_sla_regpair_addr_no_carry() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0b0111_1111);
+        cpu.store_mem(0xff82, 0b0111_1111);
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0xcb26, None);
-        assert_eq!(cpu.load_mem(0xff22), 0b1111_1110);
+        assert_eq!(cpu.load_mem(0xff82), 0b1111_1110);
         assert_eq!(cpu.flag, 0b0000_0000);
     }
 }
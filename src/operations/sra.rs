@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct ShiftRightArithmetic;
 
 impl Execute for ShiftRightArithmetic {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let src = instruction.get_operand(0)?;
 
         match *src {
@@ -47,7 +47,7 @@ impl Execute for ShiftRightArithmetic {
             },
         };
 
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -107,11 +107,11 @@ mod tests {
     #[test]
     fn test_sra_regpair_addr_no_carry() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0b1111_1110);
+        cpu.store_mem(0xff82, 0b1111_1110);
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0xcb2e, None);
-        assert_eq!(cpu.load_mem(0xff22), 0b1111_1111);
+        assert_eq!(cpu.load_mem(0xff82), 0b1111_1111);
         assert_eq!(cpu.flag, 0b0000_0000);
     }
 }
@@ -1,4 +1,4 @@
-use cpu::{CPU, CPUState};
+use cpu::CPU;
 use instructions::Instruction;
 use errors::*;
 use operations::Execute;
@@ -6,9 +6,9 @@ use operations::Execute;
 pub struct Stop;
 
 impl Execute for Stop {
-    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
-        cpu.set_state(CPUState::Stopped);
-        Ok(())
+    fn execute(_instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
+        cpu.stop();
+        Ok(true)
     }
 }
 
@@ -17,6 +17,7 @@ mod tests {
     use test_helpers::{execute_all, execute_instruction, test_cpu};
     use definition::Mnemonic;
     use cpu::CPUState;
+    use constants::*;
 
     #[test]
     fn execute_stop() {
@@ -26,7 +27,19 @@ mod tests {
     #[test]
     fn test_stop() {
         let mut cpu = test_cpu();
+        cpu.mem.borrow_mut().store_unchecked(MREG_DIV, 0x12);
         execute_instruction(&mut cpu, 0x10, None);
-        assert_eq!(cpu.state, CPUState::Stopped)
+        assert_eq!(cpu.state, CPUState::Stopped);
+        assert_eq!(cpu.load_mem(MREG_DIV), 0);
+        assert!(cpu.mem.borrow_mut().take_divider_reset());
+    }
+
+    #[test]
+    fn test_stop_during_dma() {
+        let mut cpu = test_cpu();
+        cpu.mem.borrow_mut().store_unchecked(MREG_DIV, 0x12);
+        cpu.mem.borrow_mut().store(MREG_DMA, 0xc0);
+        execute_instruction(&mut cpu, 0x10, None);
+        assert_eq!(cpu.mem.borrow().load_unchecked(MREG_DIV), 0);
     }
 }
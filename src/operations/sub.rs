@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct Subtract;
 
 impl Execute for Subtract {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let src = instruction.get_operand(0)?;
 
         match *src {
@@ -16,14 +16,12 @@ impl Execute for Subtract {
                 let a = cpu.reg[REG_A];
                 let b = cpu.reg[r];
 
-                // TODO: CHECK THIS
                 let val = a.wrapping_sub(b);
                 cpu.reg[REG_A] = val;
 
                 cpu.flag_cond(FLAG_Z, val == 0);
                 cpu.set_flag(FLAG_N);
-                cpu.set_half_carry(a as usize, b as usize);
-                cpu.set_carry(a as usize, b as usize);
+                cpu.set_sub_borrows(a, b, 0);
             },
             Operand::RegisterPairAddr(h, l) => {
                 let addr = cpu.read_reg_addr(h, l);
@@ -35,8 +33,7 @@ impl Execute for Subtract {
 
                 cpu.flag_cond(FLAG_Z, val == 0);
                 cpu.set_flag(FLAG_N);
-                cpu.set_half_carry(a as usize, b as usize);
-                cpu.set_carry(a as usize, b as usize);
+                cpu.set_sub_borrows(a, b, 0);
             },
             Operand::Immediate(BYTE) => {
                 let a = cpu.reg[REG_A];
@@ -47,8 +44,7 @@ impl Execute for Subtract {
 
                 cpu.flag_cond(FLAG_Z, val == 0);
                 cpu.set_flag(FLAG_N);
-                cpu.set_half_carry(a as usize, b as usize);
-                cpu.set_carry(a as usize, b as usize);
+                cpu.set_sub_borrows(a, b, 0);
 
             },
             _ => {
@@ -56,7 +52,7 @@ impl Execute for Subtract {
                 println!("UNEXPECTED OPERANDS {}", src);
             },
         };
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -101,10 +97,10 @@ mod tests {
     #[test]
     fn test_sub_reg_from_a_half_carry() {
         let mut cpu = test_cpu();
-        cpu.reg[REG_A] = 0x7f;
+        cpu.reg[REG_A] = 0x80;
         cpu.reg[REG_B] = 0x01;
         execute_instruction(&mut cpu, 0x90, None);
-        assert_eq!(cpu.reg[REG_A], 0x7e);
+        assert_eq!(cpu.reg[REG_A], 0x7f);
         assert_eq!(cpu.flag, 0b0110_0000);
     }
 
@@ -115,7 +111,7 @@ mod tests {
         cpu.reg[REG_B] = 0x81;
         execute_instruction(&mut cpu, 0x90, None);
         assert_eq!(cpu.reg[REG_A], 0xff);
-        assert_eq!(cpu.flag, 0b0101_0000);
+        assert_eq!(cpu.flag, 0b0111_0000);
     }
 
     #[test]
@@ -139,10 +135,10 @@ mod tests {
     #[test]
     fn test_sub_regpair_addr_from_a() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0x11);
+        cpu.store_mem(0xff82, 0x11);
         cpu.reg[REG_A] = 0x9a;
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0x96, None);
         assert_eq!(cpu.reg[REG_A], 0x89);
     }
@@ -8,7 +8,7 @@ use operations::Execute;
 pub struct Swap;
 
 impl Execute for Swap {
-    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<()> {
+    fn execute(instruction: &Instruction, cpu: &mut CPU) -> Result<bool> {
         let src = instruction.get_operand(0)?;
 
         let res = match *src {
@@ -38,7 +38,7 @@ impl Execute for Swap {
         cpu.clear_flag(FLAG_N);
         cpu.clear_flag(FLAG_H);
         cpu.clear_flag(FLAG_C);
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -85,10 +85,10 @@ mod tests {
     #[test]
     fn test_swap_regpair_addr() {
         let mut cpu = test_cpu();
-        cpu.store_mem(0xff22, 0xf0);
+        cpu.store_mem(0xff82, 0xf0);
         cpu.reg[REG_H] = 0xff;
-        cpu.reg[REG_L] = 0x22;
+        cpu.reg[REG_L] = 0x82;
         execute_instruction(&mut cpu, 0xcb36, None);
-        assert_eq!(cpu.load_mem(0xff22), 0x0f);
+        assert_eq!(cpu.load_mem(0xff82), 0x0f);
     }
 }
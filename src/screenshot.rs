@@ -0,0 +1,117 @@
+use constants::*;
+use errors::*;
+use lcd::{FrameBuffer, Shade};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[cfg(feature = "png")]
+use png;
+
+fn pixels(frame: &FrameBuffer) -> Vec<Shade> {
+    frame.iter().flat_map(|row| row.iter().cloned()).collect()
+}
+
+// Pixels as packed 8 bit RGB triplets, row by row
+fn rgb_data(pixels: &[Shade]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(pixels.len() * 3);
+    for shade in pixels {
+        let (r, g, b) = shade.rgb();
+        data.extend_from_slice(&[r, g, b]);
+    }
+    data
+}
+
+// Binary PPM (P6), which needs nothing but a short text header
+fn encode_ppm(pixels: &[Shade], width: usize, out: &mut dyn Write) -> Result<()> {
+    write!(out, "P6\n{} {}\n255\n", width, pixels.len() / width)
+        .chain_err(|| "Failed to write PPM header")?;
+    out.write_all(&rgb_data(pixels)).chain_err(|| "Failed to write PPM data")
+}
+
+#[cfg(feature = "png")]
+fn encode_png(pixels: &[Shade], width: usize, out: &mut dyn Write) -> Result<()> {
+    let height = pixels.len() / width;
+    let mut encoder = png::Encoder::new(out, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().chain_err(|| "Failed to write PNG header")?;
+    writer.write_image_data(&rgb_data(pixels)).chain_err(|| "Failed to write PNG data")
+}
+
+#[cfg(not(feature = "png"))]
+fn encode_png(_pixels: &[Shade], _width: usize, _out: &mut dyn Write) -> Result<()> {
+    bail!("PNG screenshots need the png feature, use a .ppm file instead")
+}
+
+pub fn write_ppm(frame: &FrameBuffer, out: &mut dyn Write) -> Result<()> {
+    encode_ppm(&pixels(frame), LCD_PIXELS_X, out)
+}
+
+pub fn write_png(frame: &FrameBuffer, out: &mut dyn Write) -> Result<()> {
+    encode_png(&pixels(frame), LCD_PIXELS_X, out)
+}
+
+pub fn save(frame: &FrameBuffer, path: &Path) -> Result<()> {
+    save_image(&pixels(frame), LCD_PIXELS_X, path)
+}
+
+// Saves an image of any size, given its pixels row by row. The format is
+// picked from the file extension.
+pub fn save_image(pixels: &[Shade], width: usize, path: &Path) -> Result<()> {
+    let png = match path.extension().and_then(|e| e.to_str()) {
+        Some("png") => true,
+        Some("ppm") => false,
+        _ => bail!("Unknown image format: {}", path.display()),
+    };
+
+    let file = File::create(path)
+        .chain_err(|| format!("Failed to create {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+    if png {
+        encode_png(pixels, width, &mut out)?;
+    } else {
+        encode_ppm(pixels, width, &mut out)?;
+    }
+    out.flush().chain_err(|| format!("Failed to write {}", path.display()))
+}
+
+// PNG when available, otherwise PPM
+pub fn default_extension() -> &'static str {
+    if cfg!(feature = "png") { "png" } else { "ppm" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lcd::Shade;
+
+    #[test]
+    fn test_write_ppm() {
+        let mut frame = [[Shade::Shade0; LCD_PIXELS_X]; LCD_PIXELS_Y];
+        frame[0][1] = Shade::Shade3;
+
+        let mut out = Vec::new();
+        write_ppm(&frame, &mut out).unwrap();
+
+        let header = b"P6\n160 144\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(out.len(), header.len() + LCD_PIXELS_X * LCD_PIXELS_Y * 3);
+        assert_eq!(&out[header.len()..header.len() + 6], &[155, 188, 15, 15, 56, 15]);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_write_png() {
+        let frame = [[Shade::Shade2; LCD_PIXELS_X]; LCD_PIXELS_Y];
+        let mut out = Vec::new();
+        write_png(&frame, &mut out).unwrap();
+        assert_eq!(&out[..8], b"\x89PNG\r\n\x1a\n");
+    }
+
+    #[test]
+    fn test_unknown_format() {
+        let frame = [[Shade::Shade0; LCD_PIXELS_X]; LCD_PIXELS_Y];
+        assert!(save(&frame, Path::new("screenshot.bmp")).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use serial::LinkEndpoint;
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+// Records every byte sent over the link, acting as if nothing is connected.
+// Test roms use this to report their results. Clones share the recorded
+// bytes.
+#[derive(Debug, Clone, Default)]
+pub struct CaptureLink {
+    bytes: Rc<RefCell<Vec<u8>>>,
+    echo: bool,
+}
+
+impl CaptureLink {
+    pub fn new() -> CaptureLink {
+        CaptureLink::default()
+    }
+
+    // Also prints the bytes to stdout as they arrive
+    pub fn with_echo() -> CaptureLink {
+        CaptureLink {
+            echo: true,
+            ..CaptureLink::default()
+        }
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        self.bytes.borrow().clone()
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.bytes.borrow()).into_owned()
+    }
+}
+
+impl LinkEndpoint for CaptureLink {
+    fn transfer(&mut self, value: u8) -> u8 {
+        self.bytes.borrow_mut().push(value);
+        if self.echo {
+            let mut stdout = io::stdout();
+            let _ = stdout.write_all(&[value]);
+            let _ = stdout.flush();
+        }
+        0xff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_text() {
+        let capture = CaptureLink::new();
+        let mut link = capture.clone();
+        for &b in b"Passed\n" {
+            assert_eq!(link.transfer(b), 0xff);
+        }
+        assert_eq!(capture.text(), "Passed\n");
+    }
+}
@@ -0,0 +1,11 @@
+use serial::LinkEndpoint;
+
+// No cable plugged in, so the data line floats high
+#[derive(Debug, Default)]
+pub struct Disconnected;
+
+impl LinkEndpoint for Disconnected {
+    fn transfer(&mut self, _value: u8) -> u8 {
+        0xff
+    }
+}
@@ -0,0 +1,189 @@
+mod capture;
+mod disconnected;
+mod printer;
+mod tcp;
+
+pub use self::capture::CaptureLink;
+pub use self::disconnected::Disconnected;
+pub use self::printer::{PrintedImage, Printer, PrinterLog};
+pub use self::tcp::TcpLink;
+
+use constants::*;
+
+// SC bits
+const SC_TRANSFER: u8 = 0x80;
+const SC_INTERNAL_CLOCK: u8 = 0x01;
+const SC_MASK: u8 = SC_TRANSFER | SC_INTERNAL_CLOCK;
+
+// The internal clock shifts out 8192 bits per second
+const CYCLES_PER_BYTE: usize = CLOCK_SPEED / 8192 * 8;
+// How often the link is checked for transfers clocked by the other side
+const POLL_CYCLES: usize = CLOCK_SPEED / 8192;
+
+// Whatever is plugged into the other end of the link cable
+pub trait LinkEndpoint {
+    // Exchanges a byte clocked by this side, returning the byte shifted in
+    fn transfer(&mut self, value: u8) -> u8;
+
+    // Checks for a transfer clocked by the other side, which shifts out
+    // `value` in exchange for the returned byte. Called whether or not a
+    // transfer was started on this side.
+    fn poll_external(&mut self, _value: u8) -> Option<u8> {
+        None
+    }
+}
+
+pub struct Serial {
+    sb: u8,
+    sc: u8,
+    cycles: usize,
+    poll_cycles: usize,
+    interrupt: bool,
+    link: Box<dyn LinkEndpoint>,
+}
+
+impl Serial {
+    pub fn new() -> Serial {
+        Serial {
+            sb: 0,
+            sc: 0,
+            cycles: 0,
+            poll_cycles: 0,
+            interrupt: false,
+            link: Box::new(Disconnected),
+        }
+    }
+
+    pub fn set_link(&mut self, link: Box<dyn LinkEndpoint>) {
+        self.link = link;
+    }
+
+    pub fn read(&self, addr: usize) -> u8 {
+        match addr {
+            MREG_SB => self.sb,
+            MREG_SC => self.sc,
+            _ => 0xff,
+        }
+    }
+
+    pub fn write(&mut self, addr: usize, value: u8) {
+        match addr {
+            MREG_SB => self.sb = value,
+            MREG_SC => {
+                self.sc = value & SC_MASK;
+                self.cycles = 0;
+            },
+            _ => (),
+        }
+    }
+
+    fn complete(&mut self, value: u8) {
+        self.sb = value;
+        self.sc &= !SC_TRANSFER;
+        self.interrupt = true;
+    }
+
+    pub fn tick(&mut self, cycles: usize) {
+        // The other side always gets SB in return, so that it never waits
+        // on this side to start its transfer
+        self.poll_cycles += cycles;
+        if self.poll_cycles >= POLL_CYCLES {
+            self.poll_cycles = 0;
+            if let Some(value) = self.link.poll_external(self.sb) {
+                if self.sc & SC_MASK == SC_TRANSFER {
+                    self.complete(value);
+                }
+            }
+        }
+
+        if self.sc & SC_MASK != SC_MASK {
+            return
+        }
+        self.cycles += cycles;
+        if self.cycles >= CYCLES_PER_BYTE {
+            let value = self.link.transfer(self.sb);
+            self.complete(value);
+        }
+    }
+
+    // Returns whether a transfer completed since the last call
+    pub fn take_interrupt(&mut self) -> bool {
+        let interrupt = self.interrupt;
+        self.interrupt = false;
+        interrupt
+    }
+}
+
+impl Default for Serial {
+    fn default() -> Serial {
+        Serial::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_internal_clock_transfer() {
+        let capture = CaptureLink::new();
+        let mut serial = Serial::new();
+        serial.set_link(Box::new(capture.clone()));
+
+        serial.write(MREG_SB, b'A');
+        serial.write(MREG_SC, SC_TRANSFER | SC_INTERNAL_CLOCK);
+        serial.tick(CYCLES_PER_BYTE - 4);
+        assert_eq!(serial.read(MREG_SC), SC_MASK);
+        assert!(!serial.take_interrupt());
+
+        serial.tick(4);
+        assert_eq!(serial.read(MREG_SC), SC_INTERNAL_CLOCK);
+        assert_eq!(serial.read(MREG_SB), 0xff);
+        assert!(serial.take_interrupt());
+        assert_eq!(capture.bytes(), b"A");
+    }
+
+    #[test]
+    fn test_external_clock_waits() {
+        let mut serial = Serial::new();
+        serial.write(MREG_SC, SC_TRANSFER);
+        serial.tick(CYCLES_PER_BYTE * 4);
+        // Nothing connected, so nobody drives the clock
+        assert_eq!(serial.read(MREG_SC), SC_TRANSFER);
+        assert!(!serial.take_interrupt());
+    }
+
+    struct Peer(u8);
+
+    impl LinkEndpoint for Peer {
+        fn transfer(&mut self, _value: u8) -> u8 {
+            self.0
+        }
+
+        fn poll_external(&mut self, value: u8) -> Option<u8> {
+            assert_eq!(value, 0x42);
+            Some(self.0)
+        }
+    }
+
+    #[test]
+    fn test_external_clock_transfer() {
+        let mut serial = Serial::new();
+        serial.set_link(Box::new(Peer(0x17)));
+        serial.write(MREG_SB, 0x42);
+        serial.write(MREG_SC, SC_TRANSFER);
+        serial.tick(POLL_CYCLES);
+        assert_eq!(serial.read(MREG_SB), 0x17);
+        assert!(serial.take_interrupt());
+    }
+
+    #[test]
+    fn test_external_clock_not_started() {
+        let mut serial = Serial::new();
+        serial.set_link(Box::new(Peer(0x17)));
+        serial.write(MREG_SB, 0x42);
+        serial.tick(POLL_CYCLES);
+        assert_eq!(serial.read(MREG_SB), 0x42);
+        assert!(!serial.take_interrupt());
+    }
+}
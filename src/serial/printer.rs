@@ -0,0 +1,372 @@
+use errors::*;
+use lcd::Shade;
+use screenshot;
+use serial::LinkEndpoint;
+use std::cell::RefCell;
+use std::iter;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+// Receives messages about finished prints and failed transfers
+pub type PrinterLog = Rc<dyn Fn(&str)>;
+
+const MAGIC: [u8; 2] = [0x88, 0x33];
+// Returned in place of the first of the two bytes following a packet
+const DEVICE_ID: u8 = 0x81;
+
+const COMMAND_INIT: u8 = 0x01;
+const COMMAND_PRINT: u8 = 0x02;
+const COMMAND_DATA: u8 = 0x04;
+const COMMAND_STATUS: u8 = 0x0f;
+
+const STATUS_CHECKSUM_ERROR: u8 = 0x01;
+const STATUS_PRINTING: u8 = 0x02;
+const STATUS_UNPROCESSED_DATA: u8 = 0x08;
+
+// The printer buffers up to 9 bands of 2 tile rows
+const BUFFER_BYTES: usize = 0x2280;
+const IMAGE_WIDTH: usize = 160;
+const TILES_PER_ROW: usize = IMAGE_WIDTH / 8;
+const TILE_BYTES: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Magic(usize),
+    Command,
+    Compression,
+    LengthLow,
+    LengthHigh,
+    Data,
+    ChecksumLow,
+    ChecksumHigh,
+    DeviceId,
+    Status,
+}
+
+// A printed sheet, with the palette of the print command applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintedImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Shade>,
+}
+
+impl PrintedImage {
+    // Decodes rows of 20 tiles in the 2 bits per pixel format of VRAM
+    fn decode(data: &[u8], palette: u8) -> PrintedImage {
+        let tiles = data.len() / TILE_BYTES;
+        let height = tiles.div_ceil(TILES_PER_ROW) * 8;
+        let mut pixels = vec![Shade::from_palette(palette, 0); IMAGE_WIDTH * height];
+
+        for (i, tile) in data.chunks(TILE_BYTES).take(tiles).enumerate() {
+            let tile_x = (i % TILES_PER_ROW) * 8;
+            let tile_y = (i / TILES_PER_ROW) * 8;
+            for y in 0..8 {
+                let low = tile[y * 2];
+                let high = tile[y * 2 + 1];
+                for x in 0..8 {
+                    let bit = 7 - x;
+                    let color = (((high >> bit) & 1) << 1) | ((low >> bit) & 1);
+                    let offset = (tile_y + y) * IMAGE_WIDTH + tile_x + x;
+                    pixels[offset] = Shade::from_palette(palette, color);
+                }
+            }
+        }
+
+        PrintedImage {
+            width: IMAGE_WIDTH,
+            height,
+            pixels,
+        }
+    }
+}
+
+// Game Boy Printer on the other end of the link cable. Clones share the
+// printed images.
+#[derive(Clone)]
+pub struct Printer {
+    state: State,
+    command: u8,
+    compressed: bool,
+    length: usize,
+    packet: Vec<u8>,
+    checksum: u16,
+    received_checksum: u16,
+    status: u8,
+    buffer: Vec<u8>,
+    images: Rc<RefCell<Vec<PrintedImage>>>,
+    output_dir: Option<PathBuf>,
+    log: Option<PrinterLog>,
+}
+
+impl Printer {
+    pub fn new() -> Printer {
+        Printer {
+            state: State::Magic(0),
+            command: 0,
+            compressed: false,
+            length: 0,
+            packet: Vec::new(),
+            checksum: 0,
+            received_checksum: 0,
+            status: 0,
+            buffer: Vec::new(),
+            images: Rc::new(RefCell::new(Vec::new())),
+            output_dir: None,
+            log: None,
+        }
+    }
+
+    // Also saves every printed image as print-<n>.<ext> in `dir`
+    pub fn with_output_dir(dir: PathBuf) -> Printer {
+        Printer {
+            output_dir: Some(dir),
+            ..Printer::new()
+        }
+    }
+
+    pub fn set_log(&mut self, log: PrinterLog) {
+        self.log = Some(log);
+    }
+
+    fn log(&self, message: &str) {
+        if let Some(ref log) = self.log {
+            log(message);
+        }
+    }
+
+    pub fn images(&self) -> Vec<PrintedImage> {
+        self.images.borrow().clone()
+    }
+
+    // Packets with the RLE flag set consist of runs, each starting with a
+    // byte that either repeats the following byte ((n & 0x7f) + 2) times,
+    // or is followed by (n + 1) bytes to copy as they are
+    fn decompress(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < data.len() {
+            let control = data[i] as usize;
+            i += 1;
+            if control & 0x80 != 0 {
+                if let Some(&value) = data.get(i) {
+                    out.extend(iter::repeat_n(value, (control & 0x7f) + 2));
+                }
+                i += 1;
+            } else {
+                let end = (i + control + 1).min(data.len());
+                out.extend_from_slice(&data[i..end]);
+                i = end;
+            }
+        }
+        out
+    }
+
+    fn print(&mut self, palette: u8) {
+        // A palette of 0 is treated as the usual 0xe4 by the printer
+        let palette = if palette == 0 { 0xe4 } else { palette };
+        let image = PrintedImage::decode(&self.buffer, palette);
+        self.buffer.clear();
+
+        if let Some(ref dir) = self.output_dir {
+            let count = self.images.borrow().len();
+            let name = format!("print-{}.{}", count, screenshot::default_extension());
+            let path = dir.join(name);
+            match screenshot::save_image(&image.pixels, image.width, &path) {
+                Ok(()) => self.log(&format!("Printed to {}", path.display())),
+                Err(e) => self.log(&format!("Failed to save print: {}", e)),
+            }
+        }
+        self.images.borrow_mut().push(image);
+    }
+
+    fn handle_packet(&mut self) -> Result<()> {
+        if self.checksum != self.received_checksum {
+            self.status |= STATUS_CHECKSUM_ERROR;
+            bail!("Printer packet checksum mismatch");
+        }
+        self.status &= !STATUS_CHECKSUM_ERROR;
+
+        match self.command {
+            COMMAND_INIT => {
+                self.buffer.clear();
+                self.status = 0;
+            },
+            COMMAND_DATA => {
+                let data = if self.compressed {
+                    Printer::decompress(&self.packet)
+                } else {
+                    self.packet.clone()
+                };
+                let space = BUFFER_BYTES - self.buffer.len();
+                self.buffer.extend_from_slice(&data[..data.len().min(space)]);
+            },
+            COMMAND_PRINT => {
+                let palette = self.packet.get(2).cloned().unwrap_or(0);
+                self.print(palette);
+                self.status |= STATUS_PRINTING;
+            },
+            COMMAND_STATUS => (),
+            command => bail!("Unknown printer command {:02x}", command),
+        }
+        Ok(())
+    }
+
+    // Printing completes instantly, so it is only reported until the
+    // status has been read once
+    fn take_status(&mut self) -> u8 {
+        let mut status = self.status;
+        if !self.buffer.is_empty() {
+            status |= STATUS_UNPROCESSED_DATA;
+        }
+        self.status &= !STATUS_PRINTING;
+        status
+    }
+}
+
+impl Default for Printer {
+    fn default() -> Printer {
+        Printer::new()
+    }
+}
+
+impl LinkEndpoint for Printer {
+    fn transfer(&mut self, value: u8) -> u8 {
+        let mut reply = 0x00;
+        self.state = match self.state {
+            State::Magic(i) if value == MAGIC[i] => {
+                if i + 1 < MAGIC.len() {
+                    State::Magic(i + 1)
+                } else {
+                    State::Command
+                }
+            },
+            // The mismatching byte may start the next packet
+            State::Magic(_) if value == MAGIC[0] => State::Magic(1),
+            State::Magic(_) => State::Magic(0),
+            State::Command => {
+                self.command = value;
+                self.checksum = value as u16;
+                State::Compression
+            },
+            State::Compression => {
+                self.compressed = value & 0x01 != 0;
+                self.checksum = self.checksum.wrapping_add(value as u16);
+                State::LengthLow
+            },
+            State::LengthLow => {
+                self.length = value as usize;
+                self.checksum = self.checksum.wrapping_add(value as u16);
+                State::LengthHigh
+            },
+            State::LengthHigh => {
+                self.length |= (value as usize) << 8;
+                self.checksum = self.checksum.wrapping_add(value as u16);
+                self.packet.clear();
+                if self.length == 0 { State::ChecksumLow } else { State::Data }
+            },
+            State::Data => {
+                self.packet.push(value);
+                self.checksum = self.checksum.wrapping_add(value as u16);
+                if self.packet.len() == self.length { State::ChecksumLow } else { State::Data }
+            },
+            State::ChecksumLow => {
+                self.received_checksum = value as u16;
+                State::ChecksumHigh
+            },
+            State::ChecksumHigh => {
+                self.received_checksum |= (value as u16) << 8;
+                if let Err(e) = self.handle_packet() {
+                    self.log(&e.to_string());
+                }
+                State::DeviceId
+            },
+            State::DeviceId => {
+                reply = DEVICE_ID;
+                State::Status
+            },
+            State::Status => {
+                reply = self.take_status();
+                State::Magic(0)
+            },
+        };
+        reply
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sends a packet, returning the device id and status replies
+    fn send_packet(printer: &mut Printer, command: u8, compressed: bool, data: &[u8]) -> (u8, u8) {
+        let mut bytes = vec![0x88, 0x33, command, compressed as u8,
+                             data.len() as u8, (data.len() >> 8) as u8];
+        bytes.extend_from_slice(data);
+        let checksum = bytes[2..].iter().fold(0u16, |acc, &b| acc.wrapping_add(b as u16));
+        bytes.extend_from_slice(&[checksum as u8, (checksum >> 8) as u8]);
+        for &b in &bytes {
+            assert_eq!(printer.transfer(b), 0x00);
+        }
+        (printer.transfer(0), printer.transfer(0))
+    }
+
+    #[test]
+    fn test_print() {
+        let printer = Printer::new();
+        let mut link = printer.clone();
+        assert_eq!(send_packet(&mut link, COMMAND_INIT, false, &[]), (DEVICE_ID, 0x00));
+
+        // Two rows of tiles, the first pixel of the first tile in color 3
+        let mut data = vec![0u8; 640];
+        data[0] = 0x80;
+        data[1] = 0x80;
+        assert_eq!(send_packet(&mut link, COMMAND_DATA, false, &data), (DEVICE_ID, 0x08));
+        send_packet(&mut link, COMMAND_DATA, false, &[]);
+
+        let status = send_packet(&mut link, COMMAND_PRINT, false, &[1, 0x13, 0xe4, 0x40]).1;
+        assert_eq!(status, STATUS_PRINTING);
+        assert_eq!(send_packet(&mut link, COMMAND_STATUS, false, &[]).1, 0x00);
+
+        let images = printer.images();
+        assert_eq!(images.len(), 1);
+        assert_eq!((images[0].width, images[0].height), (160, 16));
+        assert_eq!(images[0].pixels[0], Shade::Shade3);
+        assert_eq!(images[0].pixels[1], Shade::Shade0);
+    }
+
+    #[test]
+    fn test_checksum_error() {
+        let mut printer = Printer::new();
+        for &b in &[0x88, 0x33, COMMAND_STATUS, 0, 0, 0, 0x12, 0x34] {
+            printer.transfer(b);
+        }
+        assert_eq!(printer.transfer(0), DEVICE_ID);
+        assert_eq!(printer.transfer(0), STATUS_CHECKSUM_ERROR);
+    }
+
+    #[test]
+    fn test_checksum_error_is_logged() {
+        let messages = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&messages);
+        let mut printer = Printer::new();
+        printer.set_log(Rc::new(move |message: &str| log.borrow_mut().push(message.to_string())));
+        for &b in &[0x88, 0x33, COMMAND_STATUS, 0, 0, 0, 0x12, 0x34] {
+            printer.transfer(b);
+        }
+        assert_eq!(*messages.borrow(), vec!["Printer packet checksum mismatch".to_string()]);
+    }
+
+    #[test]
+    fn test_magic_resync() {
+        let mut printer = Printer::new();
+        printer.transfer(0x88);
+        assert_eq!(send_packet(&mut printer, COMMAND_STATUS, false, &[]), (DEVICE_ID, 0x00));
+    }
+
+    #[test]
+    fn test_decompress() {
+        let data = Printer::decompress(&[0x81, 0xaa, 0x01, 0x12, 0x34]);
+        assert_eq!(data, vec![0xaa, 0xaa, 0xaa, 0x12, 0x34]);
+    }
+}
@@ -0,0 +1,168 @@
+use errors::*;
+use serial::LinkEndpoint;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+// A throttled emulator only looks at the link between the bursts of
+// emulation it runs once a frame, so this covers a bit over two frames
+const REPLY_TIMEOUT_MS: u64 = 40;
+
+// Every byte is sent as a frame of a header and the byte itself. The header
+// tells requests from replies, and carries the sequence number of the
+// request so that late replies can be told apart.
+const FRAME_LEN: usize = 2;
+const FRAME_REPLY: u8 = 0x80;
+const SEQUENCE_MASK: u8 = 0x7f;
+
+// Link cable between two emulator instances. The side driving the clock
+// sends a request with its byte, which the other side answers with its SB
+// as soon as it sees it.
+#[derive(Debug)]
+pub struct TcpLink {
+    stream: TcpStream,
+    sequence: u8,
+    received: Vec<u8>,
+}
+
+impl TcpLink {
+    // Waits for the other emulator to connect
+    pub fn listen<A: ToSocketAddrs>(addr: A) -> Result<TcpLink> {
+        let listener = TcpListener::bind(addr).chain_err(|| "Failed to bind link port")?;
+        let (stream, peer) = listener.accept().chain_err(|| "Failed to accept link")?;
+        println!("Link cable connected to {}", peer);
+        TcpLink::new(stream)
+    }
+
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<TcpLink> {
+        let stream = TcpStream::connect(addr).chain_err(|| "Failed to connect link")?;
+        TcpLink::new(stream)
+    }
+
+    fn new(stream: TcpStream) -> Result<TcpLink> {
+        stream.set_nodelay(true).chain_err(|| "Failed to configure link")?;
+        Ok(TcpLink {
+            stream,
+            sequence: 0,
+            received: Vec::new(),
+        })
+    }
+
+    // Reads whatever has arrived, waiting up to `timeout` for something to
+    // arrive. A broken link behaves like an unplugged cable.
+    fn receive(&mut self, timeout: Option<Duration>) -> bool {
+        let configured = match timeout {
+            Some(timeout) => self.stream.set_read_timeout(Some(timeout)),
+            None => self.stream.set_nonblocking(true),
+        };
+        if configured.is_err() {
+            return false
+        }
+        let mut buf = [0u8; 64];
+        let result = self.stream.read(&mut buf);
+        if timeout.is_none() {
+            let _ = self.stream.set_nonblocking(false);
+        }
+        match result {
+            Ok(n) if n > 0 => {
+                self.received.extend_from_slice(&buf[..n]);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn next_frame(&mut self) -> Option<(u8, u8)> {
+        if self.received.len() < FRAME_LEN {
+            return None
+        }
+        let frame = (self.received[0], self.received[1]);
+        self.received.drain(..FRAME_LEN);
+        Some(frame)
+    }
+
+    fn reply(&mut self, header: u8, value: u8) {
+        let _ = self.stream.write_all(&[FRAME_REPLY | (header & SEQUENCE_MASK), value]);
+    }
+}
+
+impl LinkEndpoint for TcpLink {
+    fn transfer(&mut self, value: u8) -> u8 {
+        self.sequence = (self.sequence + 1) & SEQUENCE_MASK;
+        if self.stream.write_all(&[self.sequence, value]).is_err() {
+            return 0xff
+        }
+
+        let deadline = Instant::now() + Duration::from_millis(REPLY_TIMEOUT_MS);
+        loop {
+            while let Some((header, byte)) = self.next_frame() {
+                if header & FRAME_REPLY == 0 {
+                    // Both sides started a transfer at the same time
+                    self.reply(header, value);
+                } else if header & SEQUENCE_MASK == self.sequence {
+                    return byte
+                }
+                // Replies to transfers that already timed out are dropped
+            }
+            let now = Instant::now();
+            if now >= deadline || !self.receive(Some(deadline - now)) {
+                return 0xff
+            }
+        }
+    }
+
+    fn poll_external(&mut self, value: u8) -> Option<u8> {
+        self.receive(None);
+        while let Some((header, byte)) = self.next_frame() {
+            if header & FRAME_REPLY == 0 {
+                self.reply(header, value);
+                return Some(byte)
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn connected_pair() -> (TcpLink, TcpLink) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let link = TcpLink::connect(listener.local_addr().unwrap()).unwrap();
+        let peer = TcpLink::new(listener.accept().unwrap().0).unwrap();
+        (link, peer)
+    }
+
+    // Polls from another thread until a transfer comes in
+    fn answer(mut peer: TcpLink, value: u8) -> thread::JoinHandle<(TcpLink, u8)> {
+        thread::spawn(move || {
+            loop {
+                if let Some(received) = peer.poll_external(value) {
+                    return (peer, received)
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+        })
+    }
+
+    #[test]
+    fn test_tcp_exchange() {
+        let (mut link, peer) = connected_pair();
+        let peer = answer(peer, 0x99);
+        assert_eq!(link.transfer(0x42), 0x99);
+        assert_eq!(peer.join().unwrap().1, 0x42);
+    }
+
+    #[test]
+    fn test_late_reply_is_dropped() {
+        let (mut link, mut peer) = connected_pair();
+        assert_eq!(link.transfer(0x01), 0xff);
+        assert_eq!(peer.poll_external(0x11), Some(0x01));
+
+        let peer = answer(peer, 0x22);
+        assert_eq!(link.transfer(0x02), 0x22);
+        assert_eq!(peer.join().unwrap().1, 0x02);
+    }
+}
@@ -24,7 +24,7 @@ pub fn execute_all(mnemonic: Mnemonic) {
     let itr = (0..512).map(get_definition)
         .filter(|&d| d.mnemonic == mnemonic);
     for d in itr {
-        cpu.execute(&mock_instruction(&d)).expect("FAILURE");
+        cpu.execute(&mock_instruction(d)).expect("FAILURE");
     }
 }
 
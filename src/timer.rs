@@ -22,7 +22,7 @@ impl Timer {
     fn increase_timer(&mut self, cycles: usize) {
         // Load current divider
         let mut mem = self.mem.borrow_mut();
-        let tac = mem.load(MREG_TAC);
+        let tac = mem.load_unchecked(MREG_TAC);
 
         // If timer not enabled, do nothing
         if (tac & 0b100) == 0 {
@@ -31,11 +31,11 @@ impl Timer {
 
         let cycles_per_tick = TIMER_CYCLES_PER_TICK[(tac & 0b11) as usize];
 
-        let prev_count = mem.load(MREG_TIMA);
+        let prev_count = mem.load_unchecked(MREG_TIMA);
         self.timer_count += cycles;
         if self.timer_count >= cycles_per_tick {
             let count = prev_count.wrapping_add(1);
-            mem.store(MREG_TIMA, count);
+            mem.store_unchecked(MREG_TIMA, count);
             self.timer_count %= cycles_per_tick;
 
             if count < prev_count {
@@ -43,18 +43,22 @@ impl Timer {
                 mem.set_interrupt_flag(INTERRUPT_TIMER.flag);
 
                 // Set contents of TIMA to that of TMA
-                let tma = mem.load(MREG_TMA);
-                mem.store(MREG_TIMA, tma);
+                let tma = mem.load_unchecked(MREG_TMA);
+                mem.store_unchecked(MREG_TIMA, tma);
             }
         }
     }
 
     fn increase_divider(&mut self, cycles: usize) {
+        if self.mem.borrow_mut().take_divider_reset() {
+            self.divider_count = 0;
+        }
+
         let prev_count = self.divider_count;
         self.divider_count =  self.divider_count.wrapping_add(cycles as u8);
         if self.divider_count < prev_count {
             let mut mem = self.mem.borrow_mut();
-            let div = mem.load(MREG_DIV);
+            let div = mem.load_unchecked(MREG_DIV);
             mem.store_unchecked(MREG_DIV, div.wrapping_add(1));
         }
     }
@@ -68,20 +72,16 @@ impl Timer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cpu::CPU;
-    use test_helpers::test_cpu;
 
     #[test]
     fn test_timer() {
-        for i in 0..TIMER_CYCLES_PER_TICK.len() {
+        for (i, &cycles) in TIMER_CYCLES_PER_TICK.iter().enumerate() {
             let mem = Rc::new(RefCell::new(Memory::default()));
             let flag = (0b100 | i) as u8;
             mem.borrow_mut().store(MREG_TAC, flag);
 
             let mut timer = Timer::new(Rc::clone(&mem));
 
-            let cycles = TIMER_CYCLES_PER_TICK[i];
-
             for _ in 0..(CLOCK_SPEED - 1) {
                 timer.increase(1);
             }
@@ -131,4 +131,17 @@ mod tests {
         let div = mem.borrow().load(MREG_DIV);
         assert_eq!(div, 1);
     }
+
+    #[test]
+    fn test_divider_reset() {
+        let mem = Rc::new(RefCell::new(Memory::default()));
+        let mut timer = Timer::new(Rc::clone(&mem));
+
+        timer.increase_divider(200);
+        mem.borrow_mut().reset_divider();
+        timer.increase_divider(200);
+
+        assert_eq!(mem.borrow().load(MREG_DIV), 0);
+        assert_eq!(timer.divider_count, 200);
+    }
 }
@@ -0,0 +1,62 @@
+// Blargg's test roms report their results as text over the serial port.
+// Point BLARGG_ROM_DIR at a directory with the cpu_instrs and instr_timing
+// directories of the test rom collection to run them, the tests are skipped
+// when it is not set.
+extern crate gameboy;
+
+mod common;
+
+use common::{find_rom, open_rom, run_until};
+use gameboy::emulator::Emulator;
+use gameboy::serial::CaptureLink;
+
+const ROM_DIR_VAR: &str = "BLARGG_ROM_DIR";
+// Emulated seconds before a rom is considered hung
+const TIMEOUT_SECONDS: usize = 120;
+
+fn run_blargg(rom: &str) {
+    let path = match find_rom(ROM_DIR_VAR, rom) {
+        Some(path) => path,
+        None => return,
+    };
+
+    let mut file = open_rom(&path);
+    let mut emu = Emulator::new(&mut file).unwrap();
+    let serial = CaptureLink::new();
+    emu.set_link(Box::new(serial.clone()));
+
+    run_until(&mut emu, TIMEOUT_SECONDS, |_| {
+        let text = serial.text();
+        text.contains("Passed") || text.contains("Failed")
+    });
+
+    let text = serial.text();
+    assert!(text.contains("Passed"), "{} did not pass:\n{}", rom, text);
+}
+
+macro_rules! blargg_tests {
+    ($($(#[$attr:meta])* $name:ident: $rom:expr,)*) => {
+        $(
+            #[test]
+            $(#[$attr])*
+            fn $name() {
+                run_blargg($rom);
+            }
+        )*
+    }
+}
+
+blargg_tests! {
+    cpu_instrs_01_special: "cpu_instrs/individual/01-special.gb",
+    cpu_instrs_02_interrupts: "cpu_instrs/individual/02-interrupts.gb",
+    cpu_instrs_03_op_sp_hl: "cpu_instrs/individual/03-op sp,hl.gb",
+    cpu_instrs_04_op_r_imm: "cpu_instrs/individual/04-op r,imm.gb",
+    cpu_instrs_05_op_rp: "cpu_instrs/individual/05-op rp.gb",
+    cpu_instrs_06_ld_r_r: "cpu_instrs/individual/06-ld r,r.gb",
+    cpu_instrs_07_jr_jp_call_ret_rst: "cpu_instrs/individual/07-jr,jp,call,ret,rst.gb",
+    cpu_instrs_08_misc_instrs: "cpu_instrs/individual/08-misc instrs.gb",
+    cpu_instrs_09_op_r_r: "cpu_instrs/individual/09-op r,r.gb",
+    cpu_instrs_10_bit_ops: "cpu_instrs/individual/10-bit ops.gb",
+    cpu_instrs_11_op_a_hl: "cpu_instrs/individual/11-op a,(hl).gb",
+    instr_timing: "instr_timing/instr_timing.gb",
+}
@@ -0,0 +1,39 @@
+use gameboy::emulator::Emulator;
+use std::env;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+// Test roms are not distributed with the emulator. Returns None, so the
+// test can be skipped, when `var` is not set, but a rom missing from the
+// directory it points to is an error.
+pub fn find_rom(var: &str, rom: &str) -> Option<PathBuf> {
+    let dir = match env::var_os(var) {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            println!("Skipping {}, {} is not set", rom, var);
+            return None
+        },
+    };
+    let path = dir.join(rom);
+    if !path.exists() {
+        panic!("{} is set, but {} does not exist", var, path.display());
+    }
+    Some(path)
+}
+
+pub fn open_rom(path: &Path) -> File {
+    File::open(path).unwrap_or_else(|e| panic!("Failed to open {}: {}", path.display(), e))
+}
+
+// Runs a second of emulated time at a time until `done` returns true or
+// `seconds` have passed
+pub fn run_until<F: FnMut(&mut Emulator) -> bool>(emu: &mut Emulator, seconds: usize,
+                                                  mut done: F) {
+    emu.set_throttle(false);
+    for _ in 0..seconds {
+        emu.run_for(60).unwrap();
+        if done(emu) {
+            return
+        }
+    }
+}
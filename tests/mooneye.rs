@@ -0,0 +1,84 @@
+// Mooneye's test roms execute LD B,B when done, with the Fibonacci numbers
+// 3, 5, 8, 13, 21 and 34 in B, C, D, E, H and L when the test passed. Point
+// MOONEYE_ROM_DIR at the build directory of mooneye-test-suite to run them.
+// None of them has been seen passing yet, so rather than asserting on each
+// rom the results are only reported.
+extern crate gameboy;
+
+mod common;
+
+use common::{find_rom, open_rom, run_until};
+use gameboy::constants::*;
+use gameboy::emulator::Emulator;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ROM_DIR_VAR: &str = "MOONEYE_ROM_DIR";
+// Emulated seconds before a rom is considered hung
+const TIMEOUT_SECONDS: usize = 30;
+const PASS_REGISTERS: [(usize, u8); 6] = [
+    (REG_B, 3), (REG_C, 5), (REG_D, 8), (REG_E, 13), (REG_H, 21), (REG_L, 34),
+];
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Passed,
+    Failed([u8; 8]),
+    TimedOut,
+}
+
+fn run_mooneye(path: &Path) -> Outcome {
+    let mut file = open_rom(path);
+    let mut emu = Emulator::new(&mut file).unwrap();
+    emu.set_stop_at_breakpoint(true);
+    run_until(&mut emu, TIMEOUT_SECONDS, |emu| emu.at_breakpoint());
+
+    if !emu.at_breakpoint() {
+        return Outcome::TimedOut
+    }
+    let reg = emu.cpu().reg;
+    if PASS_REGISTERS.iter().all(|&(r, value)| reg[r] == value) {
+        Outcome::Passed
+    } else {
+        Outcome::Failed(reg)
+    }
+}
+
+fn collect_roms(dir: &Path, roms: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.is_dir() {
+            collect_roms(&path, roms);
+        } else if path.extension().is_some_and(|e| e == "gb") {
+            roms.push(path);
+        }
+    }
+}
+
+// Runs every rom under the acceptance directory and prints a summary, for
+// keeping track of accuracy rather than gating on it. Run with --ignored.
+#[test]
+#[ignore]
+fn mooneye_report() {
+    let dir = match find_rom(ROM_DIR_VAR, "acceptance") {
+        Some(dir) => dir,
+        None => return,
+    };
+
+    let mut roms = Vec::new();
+    collect_roms(&dir, &mut roms);
+    roms.sort();
+
+    let mut passed = 0;
+    for rom in &roms {
+        let outcome = run_mooneye(rom);
+        if outcome == Outcome::Passed {
+            passed += 1;
+        }
+        println!("{:?}: {}", outcome, rom.strip_prefix(&dir).unwrap_or(rom).display());
+    }
+    println!("{}/{} passed", passed, roms.len());
+}